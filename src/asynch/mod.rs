@@ -0,0 +1,450 @@
+use super::internal::{HumidityBuffer, SerialNumber, TemperatureBuffer};
+use super::registers::{apply_verify, heater_registers, verify_reset, RegisterWrite, VDDS};
+use super::{
+    ensure_supported, Chip, Config, DeviceId, Error, FirmwareRevision, HeaterControlRegister,
+    HeaterLevel, Measurement, MeasurementResolution, Model, RelativeHumidity, SlaveAddr,
+    Temperature, UserRegister1, VddStatus,
+};
+use super::{Retries, NO_HOLD_RETRY_DELAY_US, RESET_TIME_US};
+use super::{
+    MEASURE_HUMIDITY_HOLD, MEASURE_TEMPERATURE_HOLD, READ_ELECTRONIC_ID1, READ_ELECTRONIC_ID2,
+    READ_FIRMWARE_REVISION, READ_HEATER_REGISTER, READ_TEMPERATURE_FROM_HUMIDITY_MEASUREMENT,
+    READ_USER_REGISTER1, RESET, WRITE_HEATER_REGISTER, WRITE_USER_REGISTER1,
+};
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+
+/// Asynchronous variant of [`Si7021`](super::Si7021) for `embedded-hal-async` I2C buses.
+///
+/// While the sensor stretches the clock during a measurement the bus future is pending instead of
+/// blocking the executor.
+pub struct AsyncSi7021<I2C> {
+    i2c: I2C,
+    address: u8,
+    chip: Chip,
+    supply_check: bool,
+    write_verify: Option<u8>,
+}
+
+/// Asynchronous variant of [`HumidityReading`](super::HumidityReading)
+pub struct AsyncHumidityReading<'a, I2C> {
+    si7021: &'a mut AsyncSi7021<I2C>,
+    humidity: RelativeHumidity,
+}
+
+impl<'a, E, I2C> AsyncHumidityReading<'a, I2C>
+where
+    I2C: I2c<Error = E>,
+    E: embedded_hal::i2c::Error,
+{
+    pub fn humidity(&self) -> RelativeHumidity {
+        self.humidity
+    }
+
+    // Reads the temperature taken during this humidity measurement, releasing the driver
+    pub async fn temperature(self) -> Result<Temperature, Error<E>> {
+        self.si7021.read_temperature_rh_measurement().await
+    }
+
+    pub async fn measurement(self) -> Result<Measurement, Error<E>> {
+        let humidity = self.humidity;
+        let temperature = self.temperature().await?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+}
+
+impl<E, I2C> AsyncSi7021<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: embedded_hal::i2c::Error,
+{
+    pub fn new(i2c: I2C) -> Self {
+        Self::new_with_chip(i2c, Chip::Si7021, SlaveAddr::Default)
+    }
+
+    // Creates a driver for one of the other chips sharing the Si7021 command set
+    pub fn new_with_chip(i2c: I2C, chip: Chip, address: SlaveAddr) -> Self {
+        AsyncSi7021 {
+            i2c,
+            address: address.addr(),
+            chip,
+            supply_check: false,
+            write_verify: None,
+        }
+    }
+
+    // Creates a driver for the chip identified from its electronic ID. HTU21D and SHT21 carry no
+    // model in their ID and are rejected with UnsupportedDevice, use new_with_chip() for them.
+    pub async fn probe(i2c: I2C, address: SlaveAddr) -> Result<Self, Error<E>> {
+        let mut si7021 = Self::new_with_chip(i2c, Chip::Si7021, address);
+        let device_id = si7021.device_identity().await?;
+        si7021.chip = device_id
+            .firmware
+            .and(device_id.model.chip())
+            .ok_or(Error::UnsupportedDevice(device_id.model))?;
+        Ok(si7021)
+    }
+
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+
+    // Destroys the driver and returns the I2C bus, e.g. to hand it to another subsystem
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    // Direct access to the I2C bus while the driver is kept around
+    pub fn i2c_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
+    }
+
+    async fn write_read(&mut self, command: &[u8], buffer: &mut [u8]) -> Result<(), Error<E>> {
+        self.i2c
+            .write_read(self.address, command, buffer)
+            .await
+            .map_err(Error::I2c)?;
+        Ok(())
+    }
+
+    async fn write_register(
+        &mut self,
+        write_command: &[u8],
+        read_command: &[u8],
+        value: u8,
+        read_only: u8,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        let mut write = RegisterWrite::new(value, read_only, verify);
+        loop {
+            self.i2c
+                .write(self.address, &[write_command[0], value])
+                .await
+                .map_err(Error::I2c)?;
+            if !write.verify() {
+                return Ok(());
+            }
+            let mut buffer = [0u8; 1];
+            self.write_read(read_command, &mut buffer).await?;
+            if write.check(buffer[0])? {
+                return Ok(());
+            }
+        }
+    }
+
+    // Reads every register write back, rewriting up to the given number of retries before failing
+    // with Error::VerifyFailed. None disables verification.
+    pub fn set_write_verify(&mut self, retries: Option<u8>) {
+        self.write_verify = retries;
+    }
+
+    async fn check_supply(&mut self) -> Result<(), Error<E>> {
+        if self.supply_check && self.vdd_status().await? == VddStatus::Low {
+            return Err(Error::LowSupplyVoltage);
+        }
+        Ok(())
+    }
+
+    // Makes measurements fail with Error::LowSupplyVoltage while the VDDS bit is set, at the cost
+    // of reading User Register 1 after each measurement
+    pub fn set_supply_check(&mut self, enabled: bool) {
+        self.supply_check = enabled;
+    }
+
+    pub async fn measure_humidity(&mut self) -> Result<RelativeHumidity, Error<E>> {
+        ensure_supported(self.chip.has_humidity())?;
+        let mut humidity: HumidityBuffer<E> = HumidityBuffer::for_chip(self.chip);
+        self.write_read(MEASURE_HUMIDITY_HOLD, humidity.buf())
+            .await?;
+        self.check_supply().await?;
+        humidity.humidity()
+    }
+
+    // Returns relative humidity in % scaled by 100, i.e. 23.15% returns 2315
+    pub async fn humidity(&mut self) -> Result<i32, Error<E>> {
+        self.measure_humidity()
+            .await
+            .map(RelativeHumidity::centi_percent)
+    }
+
+    // Reads the temperature taken during the last relative humidity measurement, no new conversion
+    pub async fn read_temperature_rh_measurement(&mut self) -> Result<Temperature, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
+        self.write_read(
+            READ_TEMPERATURE_FROM_HUMIDITY_MEASUREMENT,
+            temperature.buf_nocrc(),
+        )
+        .await?;
+        temperature.temperature_nocrc()
+    }
+
+    // Returns temperature in °C scaled by 100, i.e. 23.15°C returns 2315
+    // Temperature taken during last relative humidity measurement
+    pub async fn temperature_rh_measurement(&mut self) -> Result<i32, Error<E>> {
+        self.read_temperature_rh_measurement()
+            .await
+            .map(Temperature::centi_celsius)
+    }
+
+    // Measures relative humidity and reads the temperature taken during the same conversion
+    pub async fn measure(&mut self) -> Result<Measurement, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity().await?;
+        let temperature = self.read_temperature_rh_measurement().await?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+
+    // Like measure_humidity(), but returns a handle to read the temperature of the same conversion
+    pub async fn humidity_reading(&mut self) -> Result<AsyncHumidityReading<'_, I2C>, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity().await?;
+        Ok(AsyncHumidityReading {
+            si7021: self,
+            humidity,
+        })
+    }
+
+    pub async fn measure_temperature(&mut self) -> Result<Temperature, Error<E>> {
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
+        self.write_read(MEASURE_TEMPERATURE_HOLD, temperature.buf())
+            .await?;
+        self.check_supply().await?;
+        temperature.temperature()
+    }
+
+    // Returns temperature in °C scaled by 100, i.e. 23.15°C returns 2315
+    pub async fn temperature(&mut self) -> Result<i32, Error<E>> {
+        self.measure_temperature()
+            .await
+            .map(Temperature::centi_celsius)
+    }
+
+    pub async fn serial_number(&mut self) -> Result<u64, Error<E>> {
+        let mut serial_number: SerialNumber<E> = SerialNumber::new();
+        self.write_read(READ_ELECTRONIC_ID1, serial_number.buf_id1())
+            .await?;
+        self.write_read(READ_ELECTRONIC_ID2, serial_number.buf_id2())
+            .await?;
+        serial_number.serial_number()
+    }
+
+    pub async fn firmware_revision(&mut self) -> Result<u8, Error<E>> {
+        ensure_supported(self.chip.has_firmware_revision())?;
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_FIRMWARE_REVISION, &mut buffer).await?;
+        Ok(buffer[0])
+    }
+
+    // Reads the electronic ID and firmware revision, independent of the configured chip. HTU21D
+    // and SHT21 NACK the firmware revision command, it is reported as None for them.
+    pub async fn device_identity(&mut self) -> Result<DeviceId, Error<E>> {
+        let serial = self.serial_number().await?;
+        let mut buffer = [0u8; 1];
+        let firmware = match self
+            .i2c
+            .write_read(self.address, READ_FIRMWARE_REVISION, &mut buffer)
+            .await
+        {
+            Ok(()) => Some(FirmwareRevision::from(buffer[0])),
+            Err(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => None,
+            Err(e) => return Err(Error::I2c(e)),
+        };
+        Ok(DeviceId {
+            serial,
+            model: Model::from_serial_number(serial),
+            firmware,
+        })
+    }
+
+    pub async fn reset(&mut self) -> Result<(), Error<E>> {
+        self.i2c
+            .write(self.address, RESET)
+            .await
+            .map_err(Error::I2c)?;
+        Ok(())
+    }
+
+    // Resets the sensor, waits until it responds again and checks that User Register 1 is back at
+    // its power-on value. Fails with VerifyFailed if the reset did not take effect.
+    pub async fn reset_with_delay<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset().await?;
+        delay.delay_us(RESET_TIME_US).await;
+        let mut buffer = [0u8; 1];
+        let mut retries = Retries::default();
+        loop {
+            match self
+                .i2c
+                .write_read(self.address, READ_USER_REGISTER1, &mut buffer)
+                .await
+            {
+                Ok(()) => break,
+                Err(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => retries.next()?,
+                Err(e) => return Err(Error::I2c(e)),
+            }
+            delay.delay_us(NO_HOLD_RETRY_DELAY_US).await;
+        }
+        verify_reset(self.chip, UserRegister1::from_bits(buffer[0]))
+    }
+
+    pub async fn read_user_register(&mut self) -> Result<UserRegister1, Error<E>> {
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_USER_REGISTER1, &mut buffer).await?;
+        Ok(UserRegister1::from_bits(buffer[0]))
+    }
+
+    pub async fn write_user_register(
+        &mut self,
+        user_register: UserRegister1,
+    ) -> Result<(), Error<E>> {
+        self.write_user_register_verify(user_register, self.write_verify)
+            .await
+    }
+
+    async fn write_user_register_verify(
+        &mut self,
+        user_register: UserRegister1,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        self.write_register(
+            WRITE_USER_REGISTER1,
+            READ_USER_REGISTER1,
+            user_register.bits(),
+            VDDS,
+            verify,
+        )
+        .await
+    }
+
+    pub async fn read_heater_register(&mut self) -> Result<HeaterControlRegister, Error<E>> {
+        ensure_supported(self.chip.has_heater_register())?;
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_HEATER_REGISTER, &mut buffer).await?;
+        Ok(HeaterControlRegister::from_bits(buffer[0]))
+    }
+
+    pub async fn write_heater_register(
+        &mut self,
+        heater_register: HeaterControlRegister,
+    ) -> Result<(), Error<E>> {
+        self.write_heater_register_verify(heater_register, self.write_verify)
+            .await
+    }
+
+    async fn write_heater_register_verify(
+        &mut self,
+        heater_register: HeaterControlRegister,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_heater_register())?;
+        self.write_register(
+            WRITE_HEATER_REGISTER,
+            READ_HEATER_REGISTER,
+            heater_register.bits(),
+            0,
+            verify,
+        )
+        .await
+    }
+
+    pub async fn vdd_status(&mut self) -> Result<VddStatus, Error<E>> {
+        Ok(self.read_user_register().await?.vdd_status())
+    }
+
+    // Whether the supply voltage was sufficient during the last measurement
+    pub async fn supply_ok(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.vdd_status().await? == VddStatus::Ok)
+    }
+
+    pub async fn measurement_resolution(&mut self) -> Result<MeasurementResolution, Error<E>> {
+        Ok(self.read_user_register().await?.measurement_resolution())
+    }
+
+    pub async fn set_measurement_resolution(
+        &mut self,
+        measurement_resolution: MeasurementResolution,
+    ) -> Result<(), Error<E>> {
+        let mut user_register = self.read_user_register().await?;
+        user_register.set_measurement_resolution(measurement_resolution);
+        self.write_user_register(user_register).await
+    }
+
+    // Chips without a heater control register only have a single heater level 0
+    pub async fn heater(&mut self) -> Result<Option<HeaterLevel>, Error<E>> {
+        ensure_supported(self.chip.has_heater())?;
+        if !self.read_user_register().await?.heater_enabled() {
+            return Ok(None);
+        }
+        if !self.chip.has_heater_register() {
+            return Ok(Some(HeaterLevel::MIN));
+        }
+        Ok(Some(self.read_heater_register().await?.heater_level()))
+    }
+
+    pub async fn set_heater(&mut self, heater_level: Option<HeaterLevel>) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_heater())?;
+        let has_heater_register = self.chip.has_heater_register();
+        let user_register = self.read_user_register().await?;
+        let heater_register = if has_heater_register {
+            self.read_heater_register().await?
+        } else {
+            HeaterControlRegister::default()
+        };
+        let (user_register, heater_register) =
+            heater_registers(self.chip, heater_level, user_register, heater_register)?;
+        self.write_user_register(user_register).await?;
+        if has_heater_register {
+            self.write_heater_register(heater_register).await?;
+        }
+        Ok(())
+    }
+
+    async fn heater_register_or_default(&mut self) -> Result<HeaterControlRegister, Error<E>> {
+        if self.chip.has_heater_register() {
+            self.read_heater_register().await
+        } else {
+            Ok(HeaterControlRegister::default())
+        }
+    }
+
+    pub async fn current_config(&mut self) -> Result<Config, Error<E>> {
+        let user_register = self.read_user_register().await?;
+        let heater_register = self.heater_register_or_default().await?;
+        Ok(Config::from_registers(user_register, heater_register))
+    }
+
+    // Reads each register once and only writes those whose value changes
+    pub async fn apply(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.apply_config(config, false).await
+    }
+
+    // Like apply(), but verifies written registers also when set_write_verify() is not enabled
+    pub async fn apply_verified(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.apply_config(config, true).await
+    }
+
+    async fn apply_config(&mut self, config: &Config, verified: bool) -> Result<(), Error<E>> {
+        config.ensure_supported(self.chip)?;
+        let verify = apply_verify(self.write_verify, verified);
+        let user_register = self.read_user_register().await?;
+        let heater_register = self.heater_register_or_default().await?;
+        let (user_register, heater_register) =
+            config.changes(self.chip, user_register, heater_register);
+        if let Some(user_register) = user_register {
+            self.write_user_register_verify(user_register, verify)
+                .await?;
+        }
+        if let Some(heater_register) = heater_register {
+            self.write_heater_register_verify(heater_register, verify)
+                .await?;
+        }
+        Ok(())
+    }
+}
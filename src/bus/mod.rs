@@ -0,0 +1,134 @@
+use embedded_hal::i2c::{Error, ErrorKind, I2c, Operation};
+
+use super::Si7021;
+
+/// Blocking I2C bus operations used by the driver.
+///
+/// Implemented for every embedded-hal 1.0 [`I2c`] bus. Buses implementing the embedded-hal 0.2
+/// blocking traits can be used by wrapping them in [`Eh02`] (requires the `eh02` feature).
+pub trait I2cBus {
+    type Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error>;
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    // Whether the error is the sensor not acknowledging its address, i.e. still busy converting.
+    // Buses which cannot tell report every error as a bus fault.
+    fn is_nack(_error: &Self::Error) -> bool {
+        false
+    }
+}
+
+/// Buses that can also read without writing a command first, needed for no-hold measurements.
+pub trait I2cRead: I2cBus {
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: I2c> I2cBus for T {
+    type Error = T::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        I2c::write(self, address, bytes)
+    }
+
+    // The write and read are issued as a single transaction with a repeated start
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        I2c::write_read(self, address, bytes, buffer)
+    }
+
+    fn is_nack(error: &Self::Error) -> bool {
+        matches!(error.kind(), ErrorKind::NoAcknowledge(_))
+    }
+}
+
+impl<T: I2c> I2cRead for T {
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        I2c::read(self, address, buffer)
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    // Runs the operations as a single transaction with the sensor, e.g. for commands the driver
+    // does not cover
+    pub fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result<(), super::Error<E>> {
+        self.i2c
+            .transaction(self.address, operations)
+            .map_err(super::Error::I2c)
+    }
+}
+
+/// Adapter for buses implementing the embedded-hal 0.2 blocking I2C traits.
+///
+/// Drivers for such buses are created with [`Si7021::new_eh02`], or by wrapping the bus before
+/// passing it to any other constructor. `Write` and `WriteRead` are required, no-hold measurements
+/// additionally need `Read`.
+///
+/// embedded-hal 0.2 errors carry no kind, so a sensor still converting cannot be told apart from a
+/// bus fault. No-hold reads therefore fail with the bus error instead of `WouldBlock`, and the
+/// `*_no_hold()` methods don't poll beyond the datasheet conversion time.
+#[cfg(feature = "eh02")]
+pub struct Eh02<I2C>(pub I2C);
+
+#[cfg(feature = "eh02")]
+impl<I2C> Eh02<I2C> {
+    pub fn new(i2c: I2C) -> Self {
+        Eh02(i2c)
+    }
+
+    pub fn into_inner(self) -> I2C {
+        self.0
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<E, I2C> I2cBus for Eh02<I2C>
+where
+    I2C: embedded_hal_02::blocking::i2c::Write<Error = E>
+        + embedded_hal_02::blocking::i2c::WriteRead<Error = E>,
+{
+    type Error = E;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), E> {
+        self.0.write(address, bytes)
+    }
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), E> {
+        self.0.write_read(address, bytes, buffer)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<E, I2C> I2cRead for Eh02<I2C>
+where
+    I2C: embedded_hal_02::blocking::i2c::Write<Error = E>
+        + embedded_hal_02::blocking::i2c::Read<Error = E>
+        + embedded_hal_02::blocking::i2c::WriteRead<Error = E>,
+{
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), E> {
+        self.0.read(address, buffer)
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<I2C> Si7021<Eh02<I2C>>
+where
+    Eh02<I2C>: I2cBus,
+{
+    // Creates a driver for an embedded-hal 0.2 bus, same as Si7021::new(Eh02::new(i2c))
+    pub fn new_eh02(i2c: I2C) -> Self {
+        Si7021::new(Eh02::new(i2c))
+    }
+}
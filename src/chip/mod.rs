@@ -0,0 +1,188 @@
+use super::MeasurementResolution;
+
+/// Sensors sharing the Si7021 command set
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Chip {
+    Si7006,
+    Si7013,
+    Si7020,
+    Si7021,
+    Htu21d,
+    Sht21,
+    // Temperature only sensors
+    Si7050,
+    Si7051,
+    Si7053,
+    Si7054,
+    Si7055,
+}
+
+impl Chip {
+    fn is_si705x(self) -> bool {
+        matches!(
+            self,
+            Chip::Si7050 | Chip::Si7051 | Chip::Si7053 | Chip::Si7054 | Chip::Si7055
+        )
+    }
+
+    fn is_htu21d_compatible(self) -> bool {
+        matches!(self, Chip::Htu21d | Chip::Sht21)
+    }
+
+    pub fn has_humidity(self) -> bool {
+        !self.is_si705x()
+    }
+
+    // Whether the temperature of the last humidity measurement can be read (command 0xE0)
+    pub fn has_temperature_from_humidity(self) -> bool {
+        !self.is_si705x() && !self.is_htu21d_compatible()
+    }
+
+    pub fn has_firmware_revision(self) -> bool {
+        !self.is_htu21d_compatible()
+    }
+
+    pub fn has_heater(self) -> bool {
+        !self.is_si705x()
+    }
+
+    // Whether the heater current is adjustable through the heater control register
+    pub fn has_heater_register(self) -> bool {
+        self.has_heater() && !self.is_htu21d_compatible()
+    }
+
+    // Whether the chip has the auxiliary analog input and User Register 2
+    pub fn has_analog_input(self) -> bool {
+        self == Chip::Si7013
+    }
+
+    // Whether the two least significant bits of measurements carry status instead of data
+    pub fn has_status_bits(self) -> bool {
+        self.is_htu21d_compatible()
+    }
+
+    // Returns the maximum relative humidity error in % scaled by 100, i.e. ±3% returns 300
+    pub fn humidity_accuracy(self) -> Option<i32> {
+        match self {
+            Chip::Si7006 => Some(500),
+            Chip::Si7013 | Chip::Si7021 => Some(300),
+            Chip::Si7020 => Some(400),
+            Chip::Htu21d | Chip::Sht21 => Some(200),
+            _ => None,
+        }
+    }
+
+    // Returns the maximum temperature error in °C scaled by 100, i.e. ±0.4°C returns 40
+    pub fn temperature_accuracy(self) -> i32 {
+        match self {
+            Chip::Si7006 | Chip::Si7050 => 100,
+            Chip::Si7013 | Chip::Si7020 | Chip::Si7021 | Chip::Si7054 => 40,
+            Chip::Htu21d | Chip::Sht21 | Chip::Si7053 => 30,
+            Chip::Si7051 => 10,
+            Chip::Si7055 => 50,
+        }
+    }
+
+    // Maximum time in µs for a temperature conversion at the given resolution
+    pub fn temperature_conversion_time_us(self, resolution: MeasurementResolution) -> u32 {
+        match self {
+            Chip::Htu21d => match resolution {
+                MeasurementResolution::Rh12Temp14 => 50_000,
+                MeasurementResolution::Rh8Temp12 => 13_000,
+                MeasurementResolution::Rh10Temp10 => 25_000,
+                MeasurementResolution::Rh11Temp11 => 7_000,
+            },
+            Chip::Sht21 => match resolution {
+                MeasurementResolution::Rh12Temp14 => 85_000,
+                MeasurementResolution::Rh8Temp12 => 22_000,
+                MeasurementResolution::Rh10Temp10 => 43_000,
+                MeasurementResolution::Rh11Temp11 => 11_000,
+            },
+            _ => resolution.temperature_conversion_time_us(),
+        }
+    }
+
+    // Maximum time in µs for a relative humidity conversion at the given resolution
+    pub fn humidity_conversion_time_us(self, resolution: MeasurementResolution) -> u32 {
+        match self {
+            Chip::Htu21d => match resolution {
+                MeasurementResolution::Rh12Temp14 => 16_000,
+                MeasurementResolution::Rh8Temp12 => 3_000,
+                MeasurementResolution::Rh10Temp10 => 5_000,
+                MeasurementResolution::Rh11Temp11 => 8_000,
+            },
+            Chip::Sht21 => match resolution {
+                MeasurementResolution::Rh12Temp14 => 29_000,
+                MeasurementResolution::Rh8Temp12 => 4_000,
+                MeasurementResolution::Rh10Temp10 => 9_000,
+                MeasurementResolution::Rh11Temp11 => 15_000,
+            },
+            _ => resolution.humidity_conversion_time_us(),
+        }
+    }
+}
+
+/// Model reported in the device identification byte (SNB_3) of the electronic ID
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Model {
+    Chip(Chip),
+    EngineeringSample,
+    Unknown(u8),
+}
+
+impl Model {
+    // Only Silicon Labs parts encode their model, HTU21D and SHT21 IDs map to arbitrary values
+    pub fn from_device_id(device_id: u8) -> Self {
+        match device_id {
+            0x00 | 0xff => Model::EngineeringSample,
+            0x06 => Model::Chip(Chip::Si7006),
+            0x0d => Model::Chip(Chip::Si7013),
+            0x14 => Model::Chip(Chip::Si7020),
+            0x15 => Model::Chip(Chip::Si7021),
+            0x32 => Model::Chip(Chip::Si7050),
+            0x33 => Model::Chip(Chip::Si7051),
+            0x35 => Model::Chip(Chip::Si7053),
+            0x36 => Model::Chip(Chip::Si7054),
+            0x37 => Model::Chip(Chip::Si7055),
+            id => Model::Unknown(id),
+        }
+    }
+
+    // SNB_3 is the most significant byte of the second electronic ID half
+    pub fn from_serial_number(serial_number: u64) -> Self {
+        Self::from_device_id((serial_number >> 24) as u8)
+    }
+
+    // Returns the chip if the model is supported by the driver
+    pub fn chip(self) -> Option<Chip> {
+        match self {
+            Model::Chip(chip) => Some(chip),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FirmwareRevision {
+    V1_0,
+    V2_0,
+    Unknown(u8),
+}
+
+impl From<u8> for FirmwareRevision {
+    fn from(revision: u8) -> Self {
+        match revision {
+            0xff => FirmwareRevision::V1_0,
+            0x20 => FirmwareRevision::V2_0,
+            revision => FirmwareRevision::Unknown(revision),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DeviceId {
+    pub serial: u64,
+    pub model: Model,
+    // None if the sensor does not implement the firmware revision command, as HTU21D and SHT21
+    pub firmware: Option<FirmwareRevision>,
+}
@@ -0,0 +1,111 @@
+//! Thermal comfort indices derived from a humidity and temperature reading
+//!
+//! All indices are returned as [`Temperature`], i.e. in °C scaled by 100 like the driver's
+//! measurements, and are computed with integer arithmetic only. Inputs are clamped to the sensor
+//! range as in [`psychrometrics`](crate::psychrometrics).
+
+use super::psychrometrics::{centi_vapor_pressure, clamped_humidity, clamped_temperature};
+use super::{RelativeHumidity, Temperature};
+
+// Rothfusz regression coefficients scaled by 10^8, for °F and %
+const ROTHFUSZ: [i64; 9] = [
+    -4_237_900_000,
+    204_901_523,
+    1_014_333_127,
+    -22_475_541,
+    -683_783,
+    -5_481_717,
+    122_874,
+    85_282,
+    -199,
+];
+
+// Rounded division for a positive divisor
+fn div_round(numerator: i64, denominator: i64) -> i64 {
+    (numerator + numerator.signum() * denominator / 2) / denominator
+}
+
+fn isqrt(value: u64) -> u64 {
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+    let mut value = value;
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if value >= root + bit {
+            value -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+// Full regression in °F scaled by 100, for temperature t in °F and humidity r in % scaled by 100
+fn rothfusz(t: i64, r: i64) -> i64 {
+    let [c0, c1, c2, c3, c4, c5, c6, c7, c8] = ROTHFUSZ;
+    // Products are reduced early to stay within i64 over the full sensor range
+    let tr = t * r / 100;
+    let heat_index = c0
+        + c1 * t / 100
+        + c2 * r / 100
+        + c3 * tr / 100
+        + c4 * t * t / 10_000
+        + c5 * r * r / 10_000
+        + c6 * tr * t / 10_000
+        + c7 * tr * r / 10_000
+        + c8 * tr * tr / 10_000;
+    let mut heat_index = div_round(heat_index, 1_000_000);
+    if r < 1300 && (8000..=11200).contains(&t) {
+        // Dry air: subtract (13 - RH) / 4 * sqrt((17 - |T - 95|) / 17)
+        let root = isqrt(((1700 - (t - 9500).abs()) * 100_000_000 / 1700) as u64) as i64;
+        heat_index -= div_round((1300 - r) * root, 40_000);
+    } else if r > 8500 && (8000..=8700).contains(&t) {
+        // Humid air: add (RH - 85) / 10 * (87 - T) / 5
+        heat_index += div_round((r - 8500) * (8700 - t), 5000);
+    }
+    heat_index
+}
+
+/// Heat index after the US National Weather Service, combining temperature and humidity into
+/// the perceived temperature in the shade
+///
+/// Uses Steadman's simple formula below a heat index of 80°F and the Rothfusz regression with
+/// the NWS adjustments for very dry or humid air above.
+pub fn heat_index(humidity: RelativeHumidity, temperature: Temperature) -> Temperature {
+    let celsius = Temperature::from_centi_celsius(clamped_temperature(temperature));
+    let t = i64::from(celsius.centi_fahrenheit());
+    let r = clamped_humidity(humidity);
+    // 0.5 * (T + 61 + (T - 68) * 1.2 + RH * 0.094)
+    let simple = (t + 6100 + (t - 6800) * 6 / 5 + r * 94 / 1000) / 2;
+    let heat_index = if simple + t < 16000 {
+        simple
+    } else {
+        rothfusz(t, r)
+    };
+    Temperature::from_centi_fahrenheit(heat_index as i32)
+}
+
+/// Humidex as used by Environment Canada, T + 0.5555 * (e - 10) with the vapor pressure e in hPa
+pub fn humidex(humidity: RelativeHumidity, temperature: Temperature) -> Temperature {
+    let vapor_pressure = centi_vapor_pressure(humidity, temperature);
+    let humidex = div_round(5555 * (vapor_pressure - 100_000), 1_000_000);
+    Temperature::from_centi_celsius(clamped_temperature(temperature) + humidex as i32)
+}
+
+/// Apparent temperature as used by the Australian Bureau of Meteorology,
+/// T + 0.33 * e - 0.70 * ws - 4.00 with the vapor pressure e in hPa
+///
+/// The wind speed is given in m/s scaled by 100, use 0 indoors.
+pub fn apparent_temperature(
+    humidity: RelativeHumidity,
+    temperature: Temperature,
+    wind_speed: i32,
+) -> Temperature {
+    let vapor_pressure = centi_vapor_pressure(humidity, temperature);
+    let offset = div_round(33 * vapor_pressure, 10_000) - 70 * i64::from(wind_speed) / 100 - 400;
+    Temperature::from_centi_celsius(clamped_temperature(temperature) + offset as i32)
+}
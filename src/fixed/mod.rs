@@ -0,0 +1,54 @@
+// Fixed-point arithmetic with 24 fractional bits, for derived quantities on targets without FPU
+
+pub const FRAC_BITS: u32 = 24;
+pub const ONE: i64 = 1 << FRAC_BITS;
+// ln(2) * 2^24
+const LN2: i64 = 11_629_080;
+
+pub fn mul(a: i64, b: i64) -> i64 {
+    (a * b) >> FRAC_BITS
+}
+
+pub fn div(a: i64, b: i64) -> i64 {
+    (a << FRAC_BITS) / b
+}
+
+// Converts a value scaled by 100 to fixed-point
+pub fn from_centi(value: i32) -> i64 {
+    (i64::from(value) << FRAC_BITS) / 100
+}
+
+pub fn exp(x: i64) -> i64 {
+    // exp(x) = 2^k * exp(r) with 0 <= r < ln(2), exp(r) from its Taylor series
+    let k = x.div_euclid(LN2);
+    let r = x.rem_euclid(LN2);
+    let mut term = ONE;
+    let mut sum = ONE;
+    for n in 1..12 {
+        term = mul(term, r) / n;
+        sum += term;
+    }
+    if k >= 0 {
+        sum << k
+    } else {
+        sum >> -k
+    }
+}
+
+// Natural logarithm, x must be positive
+pub fn ln(x: i64) -> i64 {
+    // ln(x) = k * ln(2) + ln(m) with 1 <= m < 2, ln(m) = 2 * atanh((m - 1) / (m + 1))
+    let k = i64::from(63 - x.leading_zeros()) - i64::from(FRAC_BITS);
+    let m = if k >= 0 { x >> k } else { x << -k };
+    let z = div(m - ONE, m + ONE);
+    let z2 = mul(z, z);
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = mul(term, z2);
+        n += 2;
+    }
+    2 * sum + k * LN2
+}
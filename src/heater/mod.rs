@@ -0,0 +1,145 @@
+use super::psychrometrics::saturation_vapor_pressure;
+use super::{Error, I2cBus, Measurement, RelativeHumidity, Si7021, Temperature};
+
+// Nominal heater current in µA at VDD = 3.3V for each heater register setting, from the Si7021
+// datasheet
+const HEATER_CURRENT_UA: [u32; 16] = [
+    3_090, 9_180, 15_240, 21_310, 27_390, 33_460, 39_530, 45_600, 51_690, 57_770, 63_840, 69_910,
+    75_980, 82_050, 88_130, 94_200,
+];
+
+/// Setting of the heater control register, one of 16 levels with increasing current draw
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct HeaterLevel(u8);
+
+impl HeaterLevel {
+    // Lowest setting, 3.09 mA. Also the only level of chips without a heater control register,
+    // whose fixed heater current differs, e.g. HTU21D and SHT21.
+    pub const MIN: HeaterLevel = HeaterLevel(0x00);
+    // Highest setting, 94.20 mA
+    pub const MAX: HeaterLevel = HeaterLevel(0x0f);
+
+    // Level from the value of the heater control register bits, None above 15
+    pub const fn new(level: u8) -> Option<Self> {
+        if level <= Self::MAX.0 {
+            Some(HeaterLevel(level))
+        } else {
+            None
+        }
+    }
+
+    // Highest level drawing at most the given current in µA, None below 3.09 mA
+    pub fn from_microamps(microamps: u32) -> Option<Self> {
+        HEATER_CURRENT_UA
+            .iter()
+            .rposition(|&current| current <= microamps)
+            .map(|level| HeaterLevel(level as u8))
+    }
+
+    // Level with the current draw closest to the given current in µA
+    pub fn nearest_microamps(microamps: u32) -> Self {
+        let level = (0..HEATER_CURRENT_UA.len())
+            .min_by_key(|&level| HEATER_CURRENT_UA[level].abs_diff(microamps))
+            .unwrap_or_default();
+        HeaterLevel(level as u8)
+    }
+
+    pub const fn level(self) -> u8 {
+        self.0
+    }
+
+    // Nominal current draw in µA of the Si70xx heater, e.g. 3090 for the lowest level. Not valid
+    // for the fixed heater of HTU21D and SHT21.
+    pub const fn microamps(self) -> u32 {
+        HEATER_CURRENT_UA[self.0 as usize]
+    }
+
+    #[cfg(feature = "float")]
+    pub fn milliamps(self) -> f32 {
+        self.microamps() as f32 / 1000.0
+    }
+}
+
+impl From<HeaterLevel> for u8 {
+    fn from(level: HeaterLevel) -> u8 {
+        level.0
+    }
+}
+
+/// Temperature rise of the die caused by the heater, for each heater level
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HeaterCompensation {
+    offsets: [Temperature; 16],
+}
+
+/// Measurement with the heater's temperature rise removed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CompensatedMeasurement {
+    pub measurement: Measurement,
+    // Whether an offset was applied because the heater was on
+    pub compensated: bool,
+}
+
+impl HeaterCompensation {
+    // Table with a calibrated temperature rise for each heater level. The rise depends on the
+    // board and enclosure, so there are no defaults: measure it by comparing readings with the
+    // heater on and off in stable conditions.
+    pub const fn new(offsets: [Temperature; 16]) -> Self {
+        HeaterCompensation { offsets }
+    }
+
+    pub fn offset(&self, level: HeaterLevel) -> Temperature {
+        self.offsets[usize::from(level.level())]
+    }
+
+    pub fn set_offset(&mut self, level: HeaterLevel, offset: Temperature) {
+        self.offsets[usize::from(level.level())] = offset;
+    }
+
+    // Converts a measurement of the heated die to ambient temperature and humidity. The humidity
+    // is scaled by the ratio of the saturation vapor pressures at die and ambient temperature, as
+    // the amount of water vapor is the same. Measurements taken with the heater off are returned
+    // unchanged.
+    pub fn compensate(
+        &self,
+        measurement: Measurement,
+        heater: Option<HeaterLevel>,
+    ) -> CompensatedMeasurement {
+        let level = match heater {
+            Some(level) => level,
+            None => {
+                return CompensatedMeasurement {
+                    measurement,
+                    compensated: false,
+                }
+            }
+        };
+        let temperature = measurement.temperature - self.offset(level);
+        let die_pressure = i64::from(saturation_vapor_pressure(measurement.temperature));
+        let ambient_pressure = i64::from(saturation_vapor_pressure(temperature)).max(1);
+        let humidity = i64::from(measurement.humidity.centi_percent()) * die_pressure;
+        let humidity = (humidity + ambient_pressure / 2) / ambient_pressure;
+        CompensatedMeasurement {
+            measurement: Measurement {
+                humidity: RelativeHumidity::from_centi_percent(humidity.clamp(0, 10000) as i32),
+                temperature,
+            },
+            compensated: true,
+        }
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    // Like measure(), but removes the heater's temperature rise for the current heater level
+    pub fn measure_compensated(
+        &mut self,
+        compensation: &HeaterCompensation,
+    ) -> Result<CompensatedMeasurement, Error<E>> {
+        let heater = self.heater()?;
+        let measurement = self.measure()?;
+        Ok(compensation.compensate(measurement, heater))
+    }
+}
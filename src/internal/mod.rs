@@ -1,14 +1,41 @@
-use super::Error;
+use super::{Chip, Error, RelativeHumidity, Temperature};
 use core::marker::PhantomData;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum MeasurementResolution {
     Rh12Temp14 = 0x00,
     Rh8Temp12 = 0x01,
+    // Temperature is actually converted with 13 bit resolution in this mode
     Rh10Temp10 = 0x80,
     Rh11Temp11 = 0x81,
 }
 
+impl MeasurementResolution {
+    /// Maximum time in µs for a temperature conversion at this resolution
+    pub fn temperature_conversion_time_us(self) -> u32 {
+        match self {
+            MeasurementResolution::Rh12Temp14 => 10_800,
+            MeasurementResolution::Rh8Temp12 => 3_800,
+            MeasurementResolution::Rh10Temp10 => 6_200,
+            MeasurementResolution::Rh11Temp11 => 2_400,
+        }
+    }
+
+    /// Maximum time in µs for a relative humidity conversion at this resolution
+    ///
+    /// Includes the temperature conversion the sensor performs alongside every humidity
+    /// measurement.
+    pub fn humidity_conversion_time_us(self) -> u32 {
+        let humidity = match self {
+            MeasurementResolution::Rh12Temp14 => 12_000,
+            MeasurementResolution::Rh8Temp12 => 3_100,
+            MeasurementResolution::Rh10Temp10 => 4_500,
+            MeasurementResolution::Rh11Temp11 => 7_000,
+        };
+        humidity + self.temperature_conversion_time_us()
+    }
+}
+
 #[derive(Default)]
 pub struct Crc8 {
     crc: u8,
@@ -77,15 +104,29 @@ impl<E> SerialNumber<E> {
     }
 }
 
-pub struct Temperature<E> {
+// Two least significant bits of a measurement, which some chips use for status
+const STATUS_BITS: u16 = 0x0003;
+
+fn measurement_raw(buffer: &[u8], status_bits: bool) -> u16 {
+    let raw = u16::from_be_bytes([buffer[0], buffer[1]]);
+    if status_bits {
+        raw & !STATUS_BITS
+    } else {
+        raw
+    }
+}
+
+pub struct TemperatureBuffer<E> {
     buffer: [u8; 3],
+    status_bits: bool,
     _marker: PhantomData<E>,
 }
 
-impl<E> Temperature<E> {
-    pub fn new() -> Self {
-        Temperature {
+impl<E> TemperatureBuffer<E> {
+    pub fn for_chip(chip: Chip) -> Self {
+        TemperatureBuffer {
             buffer: [0; 3],
+            status_bits: chip.has_status_bits(),
             _marker: PhantomData,
         }
     }
@@ -95,105 +136,74 @@ impl<E> Temperature<E> {
     pub fn buf_nocrc(&mut self) -> &mut [u8] {
         &mut self.buffer[0..2]
     }
-    pub fn temperature(&self) -> Result<i32, Error<E>> {
+    pub fn temperature(&self) -> Result<Temperature, Error<E>> {
         let mut crc = Crc8::default();
         if crc.update(&self.buffer[0..2]) != self.buffer[2] {
             return Err(Error::ChecksumFailure);
         }
         self.temperature_nocrc()
     }
-    fn buffer_temperature_raw(&self) -> i32 {
-        u16::from_be_bytes([self.buffer[0], self.buffer[1]]).into()
+    fn buffer_temperature_raw(&self) -> u16 {
+        measurement_raw(&self.buffer, self.status_bits)
     }
-    pub fn temperature_nocrc(&self) -> Result<i32, Error<E>> {
+    pub fn temperature_nocrc(&self) -> Result<Temperature, Error<E>> {
         if self.buffer[0..2] == [0x00, 0x00] {
             return Err(Error::NoPreviousHumidityMeasurement);
         }
-        Ok(17572 * self.buffer_temperature_raw() / 65536 - 4685)
+        Ok(Temperature::from_raw(self.buffer_temperature_raw()))
     }
 }
 
-pub struct Humidity<E> {
+pub struct HumidityBuffer<E> {
     buffer: [u8; 3],
+    status_bits: bool,
     _marker: PhantomData<E>,
 }
 
-impl<E> Humidity<E> {
-    pub fn new() -> Self {
-        Humidity {
+impl<E> HumidityBuffer<E> {
+    pub fn for_chip(chip: Chip) -> Self {
+        HumidityBuffer {
             buffer: [0; 3],
+            status_bits: chip.has_status_bits(),
             _marker: PhantomData,
         }
     }
     pub fn buf(&mut self) -> &mut [u8] {
         &mut self.buffer
     }
-    fn buffer_humidity_raw(&self) -> i32 {
-        u16::from_be_bytes([self.buffer[0], self.buffer[1]]).into()
+    fn buffer_humidity_raw(&self) -> u16 {
+        measurement_raw(&self.buffer, self.status_bits)
     }
-    pub fn humidity(&self) -> Result<i32, Error<E>> {
+    pub fn humidity(&self) -> Result<RelativeHumidity, Error<E>> {
         let mut crc = Crc8::default();
         if crc.update(&self.buffer[0..2]) != self.buffer[2] {
             return Err(Error::ChecksumFailure);
         }
-        let val = 12500 * self.buffer_humidity_raw() / 65536 - 600;
-        Ok(match val {
-            rh if rh > 10000 => 10000,
-            rh if rh < 0 => 0,
-            rh => rh,
-        })
+        Ok(RelativeHumidity::from_raw(self.buffer_humidity_raw()))
     }
 }
 
-pub struct UserHeaterRegister<E> {
-    register: [u8; 2],
+pub struct Analog<E> {
+    buffer: [u8; 3],
     _marker: PhantomData<E>,
 }
 
-const USER_REGISTER1: usize = 0;
-const HEATER_REGISTER: usize = 1;
-impl<E> UserHeaterRegister<E> {
+impl<E> Analog<E> {
     pub fn new() -> Self {
-        UserHeaterRegister {
-            register: [0; 2],
+        Analog {
+            buffer: [0; 3],
             _marker: PhantomData,
         }
     }
-    pub fn buf_user(&mut self) -> &mut [u8] {
-        &mut self.register[USER_REGISTER1..=USER_REGISTER1]
-    }
-    pub fn buf_heater(&mut self) -> &mut [u8] {
-        &mut self.register[HEATER_REGISTER..=HEATER_REGISTER]
-    }
-    pub fn measurement_resolution(&self) -> MeasurementResolution {
-        match self.register[USER_REGISTER1] & 0x81 {
-            0x00 => MeasurementResolution::Rh12Temp14,
-            0x01 => MeasurementResolution::Rh8Temp12,
-            0x80 => MeasurementResolution::Rh10Temp10,
-            // Use wildcard for 0x81 case, the compiler doesn't know all values have been covered
-            _ => MeasurementResolution::Rh11Temp11,
-        }
-    }
-    pub fn set_measurement_resolution(&mut self, measurement_resolution: MeasurementResolution) {
-        self.register[USER_REGISTER1] =
-            (self.register[USER_REGISTER1] & 0x7e) | measurement_resolution as u8
-    }
-    pub fn heater_on(&self) -> bool {
-        self.register[USER_REGISTER1] & 0x04 > 0
-    }
-    pub fn set_heater_state(&mut self, on: bool) {
-        self.register[USER_REGISTER1] =
-            (self.register[USER_REGISTER1] & 0xfb) | (0x04 * u8::from(on))
-    }
-    pub fn heater_level(&self) -> u8 {
-        self.register[HEATER_REGISTER] & 0x0f
+    pub fn buf(&mut self) -> &mut [u8] {
+        &mut self.buffer
     }
-    pub fn set_heater_level(&mut self, heater_level: u8) -> Result<(), Error<E>> {
-        if heater_level > 0x0f {
-            return Err(Error::InvalidHeaterLevel);
+    pub fn analog(&self) -> Result<i16, Error<E>> {
+        let mut crc = Crc8::default();
+        if crc.update(&self.buffer[0..2]) != self.buffer[2] {
+            return Err(Error::ChecksumFailure);
         }
-        self.register[HEATER_REGISTER] = (self.register[HEATER_REGISTER] & 0xf0) | heater_level;
-        Ok(())
+        Ok(i16::from_be_bytes([self.buffer[0], self.buffer[1]]))
     }
 }
 
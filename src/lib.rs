@@ -1,27 +1,109 @@
 #![no_std]
 
+#[cfg(feature = "async")]
+mod asynch;
+mod bus;
+mod chip;
+pub mod comfort;
+mod fixed;
+mod heater;
 mod internal;
+pub mod psychrometrics;
+mod reading;
+mod recovery;
+mod registers;
+mod selftest;
+mod si7013;
+mod units;
 
+#[cfg(feature = "async")]
+pub use self::asynch::{AsyncHumidityReading, AsyncSi7021};
+#[cfg(feature = "eh02")]
+pub use self::bus::Eh02;
+pub use self::bus::{I2cBus, I2cRead};
+pub use self::chip::{Chip, DeviceId, FirmwareRevision, Model};
+pub use self::heater::{CompensatedMeasurement, HeaterCompensation, HeaterLevel};
 pub use self::internal::MeasurementResolution;
-use self::internal::{Humidity, SerialNumber, Temperature, UserHeaterRegister};
-use embedded_hal::blocking::i2c;
+use self::internal::{HumidityBuffer, SerialNumber, TemperatureBuffer};
+pub use self::reading::HumidityReading;
+pub use self::recovery::{
+    Clock, CondensationRecovery, ReadingStatus, RecoveryConfig, RecoveryReading,
+};
+use self::registers::{heater_registers, verify_reset, RegisterCache, RegisterWrite};
+pub use self::registers::{Config, HeaterControlRegister, UserRegister1, VddStatus};
+pub use self::selftest::{SelfTestConfig, SelfTestReport};
+pub use self::si7013::{AnalogVoltage, ConversionTime, UserRegister2, VoltageReference, Vout};
+pub use self::units::{Measurement, RelativeHumidity, Temperature};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::ErrorKind;
 
 #[derive(Debug, PartialEq)]
 pub enum Error<E> {
     I2c(E),
     ChecksumFailure,
     NoPreviousHumidityMeasurement,
+    // The chip has no heater control register, so only HeaterLevel::MIN can be set
     InvalidHeaterLevel,
+    // The sensor did not finish a no-hold measurement within the expected time
+    Timeout,
+    // The command is not implemented by the configured chip
+    UnsupportedCommand,
+    // The device found while probing is not supported by the driver
+    UnsupportedDevice(Model),
+    // The sensor reported a low supply voltage during the measurement, see set_supply_check()
+    LowSupplyVoltage,
+    // A register read back after writing did not contain the written value
+    VerifyFailed { expected: u8, actual: u8 },
+}
+
+impl<E: embedded_hal::i2c::Error> embedded_hal::i2c::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::I2c(e) => e.kind(),
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// I2C address of the sensor
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SlaveAddr {
+    // 0x40, used by all supported chips
+    Default,
+    // 0x41, Si7013 with the AD0 pin pulled high
+    Alternative,
+    Custom(u8),
+}
+
+impl SlaveAddr {
+    pub fn addr(self) -> u8 {
+        match self {
+            SlaveAddr::Default => 0x40,
+            SlaveAddr::Alternative => 0x41,
+            SlaveAddr::Custom(address) => address,
+        }
+    }
 }
 
 pub struct Si7021<I2C> {
     i2c: I2C,
+    address: u8,
+    chip: Chip,
+    // Last resolution known to be configured, used to time no-hold measurements. Starts at the
+    // power-on default, which also has the longest conversion times.
+    measurement_resolution: MeasurementResolution,
+    // Whether measurements check the VDDS bit
+    supply_check: bool,
+    // Number of retries when a register does not read back as written, None to not verify
+    write_verify: Option<u8>,
+    // Shadow copies of User Register 1 and the heater register, None if caching is disabled
+    register_cache: Option<RegisterCache>,
 }
 
-pub type HeaterPower = u8;
-
 const MEASURE_HUMIDITY_HOLD: &[u8] = &[0xe5];
 const MEASURE_TEMPERATURE_HOLD: &[u8] = &[0xe3];
+const MEASURE_HUMIDITY_NO_HOLD: &[u8] = &[0xf5];
+const MEASURE_TEMPERATURE_NO_HOLD: &[u8] = &[0xf3];
 const READ_TEMPERATURE_FROM_HUMIDITY_MEASUREMENT: &[u8] = &[0xe0];
 const RESET: &[u8] = &[0xfe];
 const WRITE_USER_REGISTER1: &[u8] = &[0xe6];
@@ -32,32 +114,171 @@ const READ_ELECTRONIC_ID1: &[u8] = &[0xfa, 0x0f];
 const READ_ELECTRONIC_ID2: &[u8] = &[0xfc, 0xc9];
 const READ_FIRMWARE_REVISION: &[u8] = &[0x84, 0xb8];
 
+// Number of additional reads attempted when the sensor is not ready after the datasheet
+// conversion or reset time, and the delay between them
+const NO_HOLD_RETRIES: u8 = 10;
+const NO_HOLD_RETRY_DELAY_US: u32 = 1_000;
+// Maximum time until the sensor responds again after a soft reset
+const RESET_TIME_US: u32 = 15_000;
+
+// Counts the attempts to reach a sensor that NACKs while busy
+#[derive(Default)]
+struct Retries(u8);
+
+impl Retries {
+    // Fails with Timeout once NO_HOLD_RETRIES are used up, otherwise the caller waits
+    // NO_HOLD_RETRY_DELAY_US before trying again
+    fn next<E>(&mut self) -> Result<(), Error<E>> {
+        if self.0 == NO_HOLD_RETRIES {
+            return Err(Error::Timeout);
+        }
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+fn ensure_supported<E>(supported: bool) -> Result<(), Error<E>> {
+    if supported {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedCommand)
+    }
+}
+
 impl<E, I2C> Si7021<I2C>
 where
-    I2C: i2c::WriteRead<Error = E> + i2c::Write<Error = E>,
+    I2C: I2cBus<Error = E>,
 {
     pub fn new(i2c: I2C) -> Self {
-        Si7021 { i2c }
+        Self::new_with_chip(i2c, Chip::Si7021, SlaveAddr::Default)
+    }
+
+    // Creates a driver for one of the other chips sharing the Si7021 command set
+    pub fn new_with_chip(i2c: I2C, chip: Chip, address: SlaveAddr) -> Self {
+        Si7021 {
+            i2c,
+            address: address.addr(),
+            chip,
+            measurement_resolution: MeasurementResolution::Rh12Temp14,
+            supply_check: false,
+            write_verify: None,
+            register_cache: None,
+        }
+    }
+
+    // Creates a driver for the chip identified from its electronic ID. HTU21D and SHT21 carry no
+    // model in their ID and are rejected with UnsupportedDevice, use new_with_chip() for them.
+    pub fn probe(i2c: I2C, address: SlaveAddr) -> Result<Self, Error<E>> {
+        let mut si7021 = Self::new_with_chip(i2c, Chip::Si7021, address);
+        let device_id = si7021.device_identity()?;
+        si7021.chip = device_id
+            .firmware
+            .and(device_id.model.chip())
+            .ok_or(Error::UnsupportedDevice(device_id.model))?;
+        Ok(si7021)
+    }
+
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+
+    // Destroys the driver and returns the I2C bus, e.g. to hand it to another subsystem
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    // Direct access to the I2C bus while the driver is kept around
+    pub fn i2c_mut(&mut self) -> &mut I2C {
+        &mut self.i2c
     }
 
     fn write_read(&mut self, command: &[u8], buffer: &mut [u8]) -> Result<(), Error<E>> {
         self.i2c
-            .write_read(0x40, command, buffer)
+            .write_read(self.address, command, buffer)
             .map_err(Error::I2c)?;
         Ok(())
     }
 
-    // Returns relative humidity in % scaled by 100, i.e. 23.15% returns 2315
-    pub fn humidity(&mut self) -> Result<i32, Error<E>> {
-        let mut humidity: Humidity<E> = Humidity::new();
+    // Writes a single byte register, reading it back if verify is set. A mismatch of the bits
+    // outside read_only rewrites the register up to the given number of retries.
+    fn write_register(
+        &mut self,
+        write_command: &[u8],
+        read_command: &[u8],
+        value: u8,
+        read_only: u8,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        let mut write = RegisterWrite::new(value, read_only, verify);
+        loop {
+            self.i2c
+                .write(self.address, &[write_command[0], value])
+                .map_err(Error::I2c)?;
+            if !write.verify() {
+                return Ok(());
+            }
+            let mut buffer = [0u8; 1];
+            self.write_read(read_command, &mut buffer)?;
+            if write.check(buffer[0])? {
+                return Ok(());
+            }
+        }
+    }
+
+    // Fails with LowSupplyVoltage if enabled and the last measurement ran on a low supply
+    fn check_supply(&mut self) -> Result<(), Error<E>> {
+        if self.supply_check && self.vdd_status()? == VddStatus::Low {
+            return Err(Error::LowSupplyVoltage);
+        }
+        Ok(())
+    }
+
+    // Reads every register write back, rewriting up to the given number of retries before failing
+    // with Error::VerifyFailed. None disables verification.
+    pub fn set_write_verify(&mut self, retries: Option<u8>) {
+        self.write_verify = retries;
+    }
+
+    // Keeps copies of User Register 1 and the heater register instead of reading them before every
+    // change. The VDDS bit is still read from the sensor by vdd_status() and the supply check.
+    pub fn set_register_cache(&mut self, enabled: bool) {
+        self.register_cache = if enabled {
+            Some(RegisterCache::default())
+        } else {
+            None
+        };
+    }
+
+    // Drops the cached registers, e.g. when the sensor may have been reset externally
+    pub fn invalidate_cache(&mut self) {
+        if let Some(cache) = self.register_cache.as_mut() {
+            *cache = RegisterCache::default();
+        }
+    }
+
+    // Makes measurements fail with Error::LowSupplyVoltage while the VDDS bit is set, at the cost
+    // of reading User Register 1 after each measurement
+    pub fn set_supply_check(&mut self, enabled: bool) {
+        self.supply_check = enabled;
+    }
+
+    pub fn measure_humidity(&mut self) -> Result<RelativeHumidity, Error<E>> {
+        ensure_supported(self.chip.has_humidity())?;
+        let mut humidity: HumidityBuffer<E> = HumidityBuffer::for_chip(self.chip);
         self.write_read(MEASURE_HUMIDITY_HOLD, humidity.buf())?;
+        self.check_supply()?;
         humidity.humidity()
     }
 
-    // Returns temperature in °C scaled by 100, i.e. 23.15°C returns 2315
-    // Temperature taken during last relative humidity measurement
-    pub fn temperature_rh_measurement(&mut self) -> Result<i32, Error<E>> {
-        let mut temperature: Temperature<E> = Temperature::new();
+    // Returns relative humidity in % scaled by 100, i.e. 23.15% returns 2315
+    pub fn humidity(&mut self) -> Result<i32, Error<E>> {
+        self.measure_humidity().map(RelativeHumidity::centi_percent)
+    }
+
+    // Reads the temperature taken during the last relative humidity measurement, no new conversion
+    pub fn read_temperature_rh_measurement(&mut self) -> Result<Temperature, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
         self.write_read(
             READ_TEMPERATURE_FROM_HUMIDITY_MEASUREMENT,
             temperature.buf_nocrc(),
@@ -66,12 +287,52 @@ where
     }
 
     // Returns temperature in °C scaled by 100, i.e. 23.15°C returns 2315
-    pub fn temperature(&mut self) -> Result<i32, Error<E>> {
-        let mut temperature: Temperature<E> = Temperature::new();
+    // Temperature taken during last relative humidity measurement
+    pub fn temperature_rh_measurement(&mut self) -> Result<i32, Error<E>> {
+        self.read_temperature_rh_measurement()
+            .map(Temperature::centi_celsius)
+    }
+
+    pub fn measure_temperature(&mut self) -> Result<Temperature, Error<E>> {
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
         self.write_read(MEASURE_TEMPERATURE_HOLD, temperature.buf())?;
+        self.check_supply()?;
         temperature.temperature()
     }
 
+    // Returns temperature in °C scaled by 100, i.e. 23.15°C returns 2315
+    pub fn temperature(&mut self) -> Result<i32, Error<E>> {
+        self.measure_temperature().map(Temperature::centi_celsius)
+    }
+
+    // Measures relative humidity and reads the temperature taken during the same conversion
+    pub fn measure(&mut self) -> Result<Measurement, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity()?;
+        let temperature = self.read_temperature_rh_measurement()?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+
+    // Starts a humidity measurement without holding the bus, fetch it with read_humidity()
+    pub fn start_humidity_measurement(&mut self) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_humidity())?;
+        self.i2c
+            .write(self.address, MEASURE_HUMIDITY_NO_HOLD)
+            .map_err(Error::I2c)?;
+        Ok(())
+    }
+
+    // Starts a temperature measurement without holding the bus, fetch it with read_temperature()
+    pub fn start_temperature_measurement(&mut self) -> Result<(), Error<E>> {
+        self.i2c
+            .write(self.address, MEASURE_TEMPERATURE_NO_HOLD)
+            .map_err(Error::I2c)?;
+        Ok(())
+    }
+
     pub fn serial_number(&mut self) -> Result<u64, Error<E>> {
         let mut serial_number: SerialNumber<E> = SerialNumber::new();
         self.write_read(READ_ELECTRONIC_ID1, serial_number.buf_id1())?;
@@ -80,75 +341,246 @@ where
     }
 
     pub fn firmware_revision(&mut self) -> Result<u8, Error<E>> {
+        ensure_supported(self.chip.has_firmware_revision())?;
         let mut buffer = [0u8; 1];
         self.write_read(READ_FIRMWARE_REVISION, &mut buffer)?;
         Ok(buffer[0])
     }
 
+    // Reads the electronic ID and firmware revision, independent of the configured chip. HTU21D
+    // and SHT21 NACK the firmware revision command, it is reported as None for them.
+    pub fn device_identity(&mut self) -> Result<DeviceId, Error<E>> {
+        let serial = self.serial_number()?;
+        let mut buffer = [0u8; 1];
+        let firmware = match self
+            .i2c
+            .write_read(self.address, READ_FIRMWARE_REVISION, &mut buffer)
+        {
+            Ok(()) => Some(FirmwareRevision::from(buffer[0])),
+            Err(e) if I2C::is_nack(&e) => None,
+            Err(e) => return Err(Error::I2c(e)),
+        };
+        Ok(DeviceId {
+            serial,
+            model: Model::from_serial_number(serial),
+            firmware,
+        })
+    }
+
     pub fn reset(&mut self) -> Result<(), Error<E>> {
-        self.i2c.write(0x40, RESET).map_err(Error::I2c)?;
+        self.i2c.write(self.address, RESET).map_err(Error::I2c)?;
+        self.measurement_resolution = MeasurementResolution::Rh12Temp14;
+        if let Some(cache) = self.register_cache.as_mut() {
+            *cache = RegisterCache::after_reset(self.chip);
+        }
         Ok(())
     }
 
+    // Resets the sensor, waits until it responds again and checks that User Register 1 is back at
+    // its power-on value. Fails with VerifyFailed if the reset did not take effect.
+    pub fn reset_with_delay<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.reset()?;
+        delay.delay_us(RESET_TIME_US);
+        let mut buffer = [0u8; 1];
+        let mut retries = Retries::default();
+        loop {
+            match self
+                .i2c
+                .write_read(self.address, READ_USER_REGISTER1, &mut buffer)
+            {
+                Ok(()) => break,
+                Err(e) if I2C::is_nack(&e) => retries.next()?,
+                Err(e) => return Err(Error::I2c(e)),
+            }
+            delay.delay_us(NO_HOLD_RETRY_DELAY_US);
+        }
+        let user_register = UserRegister1::from_bits(buffer[0]);
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.user_register = Some(user_register);
+        }
+        self.measurement_resolution = user_register.measurement_resolution();
+        verify_reset(self.chip, user_register)
+    }
+
+    pub fn vdd_status(&mut self) -> Result<VddStatus, Error<E>> {
+        Ok(self.read_user_register()?.vdd_status())
+    }
+
+    // Whether the supply voltage was sufficient during the last measurement
+    pub fn supply_ok(&mut self) -> Result<bool, Error<E>> {
+        Ok(self.vdd_status()? == VddStatus::Ok)
+    }
+
     pub fn measurement_resolution(&mut self) -> Result<MeasurementResolution, Error<E>> {
-        let mut user_heater_register: UserHeaterRegister<E> = UserHeaterRegister::new();
-        self.write_read(READ_USER_REGISTER1, user_heater_register.buf_user())?;
-        Ok(user_heater_register.measurement_resolution())
+        Ok(self.cached_user_register()?.measurement_resolution())
     }
 
     pub fn set_measurement_resolution(
         &mut self,
         measurement_resolution: MeasurementResolution,
     ) -> Result<(), Error<E>> {
-        let mut user_heater_register: UserHeaterRegister<E> = UserHeaterRegister::new();
-        self.write_read(READ_USER_REGISTER1, user_heater_register.buf_user())?;
-        user_heater_register.set_measurement_resolution(measurement_resolution);
-        self.i2c
-            .write(
-                0x40,
-                &[WRITE_USER_REGISTER1[0], user_heater_register.buf_user()[0]],
-            )
-            .map_err(Error::I2c)?;
-        Ok(())
+        let mut user_register = self.cached_user_register()?;
+        user_register.set_measurement_resolution(measurement_resolution);
+        self.write_user_register(user_register)
     }
 
-    pub fn heater(&mut self) -> Result<Option<HeaterPower>, Error<E>> {
-        let mut user_heater_register: UserHeaterRegister<E> = UserHeaterRegister::new();
-        self.write_read(READ_USER_REGISTER1, user_heater_register.buf_user())?;
-        Ok(if user_heater_register.heater_on() {
-            self.write_read(READ_HEATER_REGISTER, user_heater_register.buf_heater())?;
-            Some(user_heater_register.heater_level())
+    // Chips without a heater control register only have a single heater level 0
+    pub fn heater(&mut self) -> Result<Option<HeaterLevel>, Error<E>> {
+        ensure_supported(self.chip.has_heater())?;
+        if !self.cached_user_register()?.heater_enabled() {
+            return Ok(None);
+        }
+        if !self.chip.has_heater_register() {
+            return Ok(Some(HeaterLevel::MIN));
+        }
+        Ok(Some(self.cached_heater_register()?.heater_level()))
+    }
+
+    pub fn set_heater(&mut self, heater_level: Option<HeaterLevel>) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_heater())?;
+        let has_heater_register = self.chip.has_heater_register();
+        let user_register = self.cached_user_register()?;
+        let heater_register = if has_heater_register {
+            self.cached_heater_register()?
         } else {
-            None
+            HeaterControlRegister::default()
+        };
+        let (user_register, heater_register) =
+            heater_registers(self.chip, heater_level, user_register, heater_register)?;
+        self.write_user_register(user_register)?;
+        if has_heater_register {
+            self.write_heater_register(heater_register)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cRead<Error = E>,
+{
+    // Reads the result of a no-hold measurement, the sensor NACKs while still converting
+    fn read_no_hold(&mut self, buffer: &mut [u8]) -> nb::Result<(), Error<E>> {
+        self.i2c.read(self.address, buffer).map_err(|e| {
+            if I2C::is_nack(&e) {
+                nb::Error::WouldBlock
+            } else {
+                nb::Error::Other(Error::I2c(e))
+            }
         })
     }
 
-    pub fn set_heater(&mut self, heater_power: Option<HeaterPower>) -> Result<(), Error<E>> {
-        let mut user_heater_register: UserHeaterRegister<E> = UserHeaterRegister::new();
-        self.write_read(READ_USER_REGISTER1, user_heater_register.buf_user())?;
-        self.write_read(READ_HEATER_REGISTER, user_heater_register.buf_heater())?;
-        match heater_power {
-            Some(v) => {
-                user_heater_register.set_heater_level(v)?;
-                user_heater_register.set_heater_state(true)
+    // Starts a no-hold measurement, waits for the conversion and polls until the sensor responds
+    fn convert_no_hold<D: DelayNs>(
+        &mut self,
+        command: &[u8],
+        conversion_time_us: u32,
+        buffer: &mut [u8],
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.i2c.write(self.address, command).map_err(Error::I2c)?;
+        delay.delay_us(conversion_time_us);
+        let mut retries = Retries::default();
+        loop {
+            match self.read_no_hold(buffer) {
+                Ok(()) => return Ok(()),
+                Err(nb::Error::WouldBlock) => retries.next()?,
+                Err(nb::Error::Other(e)) => return Err(e),
             }
-            None => user_heater_register.set_heater_state(false),
+            delay.delay_us(NO_HOLD_RETRY_DELAY_US);
         }
-        self.i2c
-            .write(
-                0x40,
-                &[WRITE_USER_REGISTER1[0], user_heater_register.buf_user()[0]],
-            )
-            .map_err(Error::I2c)?;
-        self.i2c
-            .write(
-                0x40,
-                &[
-                    WRITE_HEATER_REGISTER[0],
-                    user_heater_register.buf_heater()[0],
-                ],
-            )
-            .map_err(Error::I2c)?;
-        Ok(())
+    }
+
+    // Like measure(), but without holding the bus during the conversion
+    pub fn measure_no_hold<D: DelayNs>(&mut self, delay: &mut D) -> Result<Measurement, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity_no_hold(delay)?;
+        let temperature = self.read_temperature_rh_measurement()?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+
+    // Like measure_humidity(), but without holding the bus during the conversion
+    pub fn measure_humidity_no_hold<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<RelativeHumidity, Error<E>> {
+        ensure_supported(self.chip.has_humidity())?;
+        let mut humidity: HumidityBuffer<E> = HumidityBuffer::for_chip(self.chip);
+        let conversion_time_us = self
+            .chip
+            .humidity_conversion_time_us(self.measurement_resolution);
+        self.convert_no_hold(
+            MEASURE_HUMIDITY_NO_HOLD,
+            conversion_time_us,
+            humidity.buf(),
+            delay,
+        )?;
+        self.check_supply()?;
+        humidity.humidity()
+    }
+
+    // Like humidity(), but without holding the bus during the conversion
+    pub fn humidity_no_hold<D: DelayNs>(&mut self, delay: &mut D) -> Result<i32, Error<E>> {
+        self.measure_humidity_no_hold(delay)
+            .map(RelativeHumidity::centi_percent)
+    }
+
+    // Like measure_temperature(), but without holding the bus during the conversion
+    pub fn measure_temperature_no_hold<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Temperature, Error<E>> {
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
+        let conversion_time_us = self
+            .chip
+            .temperature_conversion_time_us(self.measurement_resolution);
+        self.convert_no_hold(
+            MEASURE_TEMPERATURE_NO_HOLD,
+            conversion_time_us,
+            temperature.buf(),
+            delay,
+        )?;
+        self.check_supply()?;
+        temperature.temperature()
+    }
+
+    // Like temperature(), but without holding the bus during the conversion
+    pub fn temperature_no_hold<D: DelayNs>(&mut self, delay: &mut D) -> Result<i32, Error<E>> {
+        self.measure_temperature_no_hold(delay)
+            .map(Temperature::centi_celsius)
+    }
+
+    // Returns the relative humidity from start_humidity_measurement(), or WouldBlock while the
+    // conversion is still in progress
+    pub fn read_humidity_measurement(&mut self) -> nb::Result<RelativeHumidity, Error<E>> {
+        ensure_supported(self.chip.has_humidity())?;
+        let mut humidity: HumidityBuffer<E> = HumidityBuffer::for_chip(self.chip);
+        self.read_no_hold(humidity.buf())?;
+        self.check_supply()?;
+        Ok(humidity.humidity()?)
+    }
+
+    // Like read_humidity_measurement(), in % scaled by 100
+    pub fn read_humidity(&mut self) -> nb::Result<i32, Error<E>> {
+        self.read_humidity_measurement()
+            .map(RelativeHumidity::centi_percent)
+    }
+
+    // Returns the temperature from start_temperature_measurement(), or WouldBlock while the
+    // conversion is still in progress
+    pub fn read_temperature_measurement(&mut self) -> nb::Result<Temperature, Error<E>> {
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
+        self.read_no_hold(temperature.buf())?;
+        self.check_supply()?;
+        Ok(temperature.temperature()?)
+    }
+
+    // Like read_temperature_measurement(), in °C scaled by 100
+    pub fn read_temperature(&mut self) -> nb::Result<i32, Error<E>> {
+        self.read_temperature_measurement()
+            .map(Temperature::centi_celsius)
     }
 }
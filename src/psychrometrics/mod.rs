@@ -0,0 +1,207 @@
+//! Moisture quantities derived from a humidity and temperature reading
+//!
+//! Vapor pressures follow the Magnus formula with the coefficients from Sonntag (1990), valid
+//! from -45°C to 60°C over water and -65°C to 0°C over ice. The integer functions use fixed-point
+//! arithmetic, the `_f32` variants require the `float` feature.
+//!
+//! Mixing ratio and specific humidity depend on the air pressure in Pa, `None` assumes the
+//! standard atmosphere.
+//!
+//! Inputs are clamped to what the sensor can report, 0-100% humidity and -46.85°C to 128.86°C,
+//! so the integer functions cannot overflow or divide by zero.
+
+use super::fixed;
+use super::{RelativeHumidity, Temperature};
+
+// Magnus coefficients scaled by 100: a, b in °C and saturation vapor pressure at 0°C in Pa
+const WATER_A: i32 = 1762;
+const WATER_B: i32 = 24312;
+const ICE_A: i32 = 2246;
+const ICE_B: i32 = 27262;
+const C: i64 = 61120;
+// Ratio of the molar masses of water vapor and dry air, scaled by 100000
+const EPSILON: i64 = 62198;
+// Specific gas constant of water vapor in J/(kg·K), scaled by 100
+const WATER_VAPOR_R: i64 = 46152;
+// Measurable range of the sensor in °C scaled by 100, from the lowest and highest code
+const MIN_TEMPERATURE: i32 = -4685;
+const MAX_TEMPERATURE: i32 = 12886;
+
+/// Standard atmosphere in Pa, used when no pressure is given
+pub const STANDARD_PRESSURE: i32 = 101_325;
+
+pub(crate) fn clamped_temperature(temperature: Temperature) -> i32 {
+    temperature
+        .centi_celsius()
+        .clamp(MIN_TEMPERATURE, MAX_TEMPERATURE)
+}
+
+pub(crate) fn clamped_humidity(humidity: RelativeHumidity) -> i64 {
+    i64::from(humidity.centi_percent().clamp(0, 10000))
+}
+
+// a * T / (b + T) in fixed-point
+fn magnus_exponent(temperature: Temperature, a: i32, b: i32) -> i64 {
+    let t = i64::from(clamped_temperature(temperature));
+    i64::from(a) * t * fixed::ONE / (100 * (i64::from(b) + t))
+}
+
+// Saturation vapor pressure in Pa in fixed-point
+fn saturation_pressure_fixed(exponent: i64) -> i64 {
+    C * fixed::exp(exponent) / 100
+}
+
+fn saturation_pressure(exponent: i64) -> i32 {
+    ((saturation_pressure_fixed(exponent) + fixed::ONE / 2) >> fixed::FRAC_BITS) as i32
+}
+
+// Actual vapor pressure in Pa scaled by 100
+pub(crate) fn centi_vapor_pressure(humidity: RelativeHumidity, temperature: Temperature) -> i64 {
+    let saturation = saturation_pressure_fixed(magnus_exponent(temperature, WATER_A, WATER_B));
+    (saturation * clamped_humidity(humidity) / 100 + fixed::ONE / 2) >> fixed::FRAC_BITS
+}
+
+// Rounded division of non-negative values, saturated at i32::MAX for a non-positive divisor
+fn div_round(numerator: i64, denominator: i64) -> i32 {
+    if denominator <= 0 {
+        return i32::MAX;
+    }
+    ((numerator + denominator / 2) / denominator).min(i64::from(i32::MAX)) as i32
+}
+
+// ln(e / c) of the actual vapor pressure e, None without any humidity
+fn vapor_pressure_ln(humidity: RelativeHumidity, temperature: Temperature) -> Option<i64> {
+    let humidity = clamped_humidity(humidity);
+    if humidity == 0 {
+        return None;
+    }
+    let ln_humidity = fixed::ln(humidity * fixed::ONE / 10000);
+    Some(ln_humidity + magnus_exponent(temperature, WATER_A, WATER_B))
+}
+
+// Inverts the Magnus formula
+fn magnus_temperature(gamma: i64, a: i32, b: i32) -> Temperature {
+    let numerator = i64::from(b) * gamma;
+    let denominator = fixed::from_centi(a) - gamma;
+    let centi_celsius = (numerator + numerator.signum() * denominator / 2) / denominator;
+    Temperature::from_centi_celsius(centi_celsius as i32)
+}
+
+/// Saturation vapor pressure over water in Pa
+pub fn saturation_vapor_pressure(temperature: Temperature) -> i32 {
+    saturation_pressure(magnus_exponent(temperature, WATER_A, WATER_B))
+}
+
+/// Saturation vapor pressure over ice in Pa
+pub fn saturation_vapor_pressure_ice(temperature: Temperature) -> i32 {
+    saturation_pressure(magnus_exponent(temperature, ICE_A, ICE_B))
+}
+
+/// Actual vapor pressure in Pa
+pub fn vapor_pressure(humidity: RelativeHumidity, temperature: Temperature) -> i32 {
+    let saturation = i64::from(saturation_vapor_pressure(temperature));
+    ((saturation * clamped_humidity(humidity) + 5000) / 10000) as i32
+}
+
+/// Temperature at which the air would be saturated over water, None at 0% humidity
+pub fn dew_point(humidity: RelativeHumidity, temperature: Temperature) -> Option<Temperature> {
+    vapor_pressure_ln(humidity, temperature)
+        .map(|gamma| magnus_temperature(gamma, WATER_A, WATER_B))
+}
+
+/// Temperature at which the air would be saturated over ice, None at 0% humidity
+///
+/// The sensor reports humidity relative to water also below 0°C. Use the frost point instead of
+/// the dew point where the result is below 0°C.
+pub fn frost_point(humidity: RelativeHumidity, temperature: Temperature) -> Option<Temperature> {
+    vapor_pressure_ln(humidity, temperature).map(|gamma| magnus_temperature(gamma, ICE_A, ICE_B))
+}
+
+/// Absolute humidity in g/m³ scaled by 100, i.e. 11.48 g/m³ returns 1148
+pub fn absolute_humidity(humidity: RelativeHumidity, temperature: Temperature) -> i32 {
+    // Ideal gas law for water vapor, rho = e / (Rv * T)
+    let vapor_pressure = centi_vapor_pressure(humidity, temperature);
+    let centi_kelvin = i64::from(clamped_temperature(temperature)) + 27315;
+    div_round(vapor_pressure * 10_000_000, WATER_VAPOR_R * centi_kelvin)
+}
+
+/// Mass of water vapor per mass of dry air in g/kg scaled by 100, i.e. 9.85 g/kg returns 985
+pub fn mixing_ratio(
+    humidity: RelativeHumidity,
+    temperature: Temperature,
+    pressure: Option<i32>,
+) -> i32 {
+    // w = epsilon * e / (p - e)
+    let vapor_pressure = centi_vapor_pressure(humidity, temperature);
+    let pressure = 100 * i64::from(pressure.unwrap_or(STANDARD_PRESSURE));
+    div_round(EPSILON * vapor_pressure, pressure - vapor_pressure)
+}
+
+/// Mass of water vapor per mass of moist air in g/kg scaled by 100, i.e. 9.76 g/kg returns 976
+pub fn specific_humidity(
+    humidity: RelativeHumidity,
+    temperature: Temperature,
+    pressure: Option<i32>,
+) -> i32 {
+    // q = epsilon * e / (p - (1 - epsilon) * e)
+    let vapor_pressure = centi_vapor_pressure(humidity, temperature);
+    let pressure = 100 * i64::from(pressure.unwrap_or(STANDARD_PRESSURE));
+    div_round(
+        EPSILON * vapor_pressure * 100_000,
+        100_000 * pressure - (100_000 - EPSILON) * vapor_pressure,
+    )
+}
+
+/// Saturation vapor pressure over water in Pa for a temperature in °C
+#[cfg(feature = "float")]
+pub fn saturation_vapor_pressure_f32(celsius: f32) -> f32 {
+    611.2 * libm::expf(17.62 * celsius / (243.12 + celsius))
+}
+
+/// Saturation vapor pressure over ice in Pa for a temperature in °C
+#[cfg(feature = "float")]
+pub fn saturation_vapor_pressure_ice_f32(celsius: f32) -> f32 {
+    611.2 * libm::expf(22.46 * celsius / (272.62 + celsius))
+}
+
+/// Actual vapor pressure in Pa for a relative humidity in % and temperature in °C
+#[cfg(feature = "float")]
+pub fn vapor_pressure_f32(percent: f32, celsius: f32) -> f32 {
+    saturation_vapor_pressure_f32(celsius) * percent / 100.0
+}
+
+/// Dew point in °C for a relative humidity in % and temperature in °C
+#[cfg(feature = "float")]
+pub fn dew_point_f32(percent: f32, celsius: f32) -> f32 {
+    let gamma = libm::logf(percent / 100.0) + 17.62 * celsius / (243.12 + celsius);
+    243.12 * gamma / (17.62 - gamma)
+}
+
+/// Frost point in °C for a relative humidity in % and temperature in °C
+#[cfg(feature = "float")]
+pub fn frost_point_f32(percent: f32, celsius: f32) -> f32 {
+    let gamma = libm::logf(percent / 100.0) + 17.62 * celsius / (243.12 + celsius);
+    272.62 * gamma / (22.46 - gamma)
+}
+
+/// Absolute humidity in g/m³ for a relative humidity in % and temperature in °C
+#[cfg(feature = "float")]
+pub fn absolute_humidity_f32(percent: f32, celsius: f32) -> f32 {
+    vapor_pressure_f32(percent, celsius) * 1000.0 / (461.52 * (celsius + 273.15))
+}
+
+/// Mixing ratio in g/kg for a relative humidity in %, temperature in °C and pressure in Pa
+#[cfg(feature = "float")]
+pub fn mixing_ratio_f32(percent: f32, celsius: f32, pressure: Option<f32>) -> f32 {
+    let vapor_pressure = vapor_pressure_f32(percent, celsius);
+    let pressure = pressure.unwrap_or(STANDARD_PRESSURE as f32);
+    621.98 * vapor_pressure / (pressure - vapor_pressure)
+}
+
+/// Specific humidity in g/kg for a relative humidity in %, temperature in °C and pressure in Pa
+#[cfg(feature = "float")]
+pub fn specific_humidity_f32(percent: f32, celsius: f32, pressure: Option<f32>) -> f32 {
+    let vapor_pressure = vapor_pressure_f32(percent, celsius);
+    let pressure = pressure.unwrap_or(STANDARD_PRESSURE as f32);
+    621.98 * vapor_pressure / (pressure - 0.37802 * vapor_pressure)
+}
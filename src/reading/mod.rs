@@ -0,0 +1,69 @@
+use super::{
+    ensure_supported, DelayNs, Error, I2cBus, I2cRead, Measurement, RelativeHumidity, Si7021,
+    Temperature,
+};
+
+/// Relative humidity measurement that still holds on to the driver
+///
+/// The temperature taken during the conversion can only be read through this handle, so it can
+/// neither be requested before a humidity measurement nor after another command replaced it.
+pub struct HumidityReading<'a, I2C> {
+    si7021: &'a mut Si7021<I2C>,
+    humidity: RelativeHumidity,
+}
+
+impl<'a, E, I2C> HumidityReading<'a, I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    pub fn humidity(&self) -> RelativeHumidity {
+        self.humidity
+    }
+
+    // Reads the temperature taken during this humidity measurement, releasing the driver
+    pub fn temperature(self) -> Result<Temperature, Error<E>> {
+        self.si7021.read_temperature_rh_measurement()
+    }
+
+    pub fn measurement(self) -> Result<Measurement, Error<E>> {
+        let humidity = self.humidity;
+        let temperature = self.temperature()?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    // Like measure_humidity(), but returns a handle to read the temperature of the same conversion
+    pub fn humidity_reading(&mut self) -> Result<HumidityReading<'_, I2C>, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity()?;
+        Ok(HumidityReading {
+            si7021: self,
+            humidity,
+        })
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cRead<Error = E>,
+{
+    // Like humidity_reading(), but without holding the bus during the conversion
+    pub fn humidity_reading_no_hold<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<HumidityReading<'_, I2C>, Error<E>> {
+        ensure_supported(self.chip.has_temperature_from_humidity())?;
+        let humidity = self.measure_humidity_no_hold(delay)?;
+        Ok(HumidityReading {
+            si7021: self,
+            humidity,
+        })
+    }
+}
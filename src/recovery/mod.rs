@@ -0,0 +1,194 @@
+use super::{Config, Error, HeaterLevel, I2cBus, Measurement, RelativeHumidity, Si7021};
+
+/// Monotonic time source in milliseconds, e.g. backed by a hardware timer
+///
+/// Implemented for closures so `|| timer.now_ms()` can be passed directly.
+pub trait Clock {
+    fn now_ms(&mut self) -> u64;
+}
+
+impl<F: FnMut() -> u64> Clock for F {
+    fn now_ms(&mut self) -> u64 {
+        self()
+    }
+}
+
+/// Settings of the heat-and-dry cycle run by [`CondensationRecovery`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RecoveryConfig {
+    // Humidity at or above which the sensor is considered saturated
+    pub saturation_threshold: RelativeHumidity,
+    // How long the sensor has to stay saturated before the heater is switched on
+    pub saturation_ms: u64,
+    pub heater_level: HeaterLevel,
+    pub heating_ms: u64,
+    // Time for the die to return to ambient temperature after heating
+    pub cooldown_ms: u64,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        RecoveryConfig {
+            saturation_threshold: RelativeHumidity::from_centi_percent(9900),
+            saturation_ms: 300_000,
+            heater_level: HeaterLevel::MAX,
+            heating_ms: 60_000,
+            cooldown_ms: 30_000,
+        }
+    }
+}
+
+/// Whether a measurement taken through [`CondensationRecovery`] reflects the ambient air
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReadingStatus {
+    Valid,
+    // Humidity at or above the saturation threshold, possibly caused by condensation
+    Saturated,
+    // Taken while the heater dries the sensor
+    Heating,
+    // Taken while the die is still warmer than ambient after heating
+    CoolingDown,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RecoveryReading {
+    pub measurement: Measurement,
+    pub status: ReadingStatus,
+}
+
+impl RecoveryReading {
+    /// The measurement if it reflects the ambient air, None while saturated or recovering
+    pub fn valid(self) -> Option<Measurement> {
+        match self.status {
+            ReadingStatus::Valid => Some(self.measurement),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Monitoring { saturated_since: Option<u64> },
+    // Keeps the configuration found before heating, restored afterwards
+    Heating { since: u64, previous: Config },
+    CoolingDown { since: u64 },
+}
+
+/// Recovers a sensor from condensation by running the heater after sustained saturation
+///
+/// Call [`measure()`](CondensationRecovery::measure) periodically instead of the driver's
+/// `measure()`. Once readings stay saturated for the configured time the heater is switched on,
+/// and after heating and cooling down the heater configuration found before is restored.
+pub struct CondensationRecovery<C> {
+    config: RecoveryConfig,
+    clock: C,
+    state: State,
+}
+
+impl<C: Clock> CondensationRecovery<C> {
+    pub fn new(config: RecoveryConfig, clock: C) -> Self {
+        CondensationRecovery {
+            config,
+            clock,
+            state: State::Monitoring {
+                saturated_since: None,
+            },
+        }
+    }
+
+    pub fn config(&self) -> &RecoveryConfig {
+        &self.config
+    }
+
+    /// Whether a heat-and-dry cycle including its cooldown is in progress
+    pub fn is_recovering(&self) -> bool {
+        !matches!(self.state, State::Monitoring { .. })
+    }
+
+    /// Takes a measurement and advances the heat-and-dry cycle
+    pub fn measure<E, I2C>(&mut self, si7021: &mut Si7021<I2C>) -> Result<RecoveryReading, Error<E>>
+    where
+        I2C: I2cBus<Error = E>,
+    {
+        let now = self.clock.now_ms();
+        self.finish_phase(si7021, now)?;
+        let measurement = si7021.measure()?;
+        let status = match self.state {
+            State::Monitoring { saturated_since } => {
+                if measurement.humidity < self.config.saturation_threshold {
+                    self.state = State::Monitoring {
+                        saturated_since: None,
+                    };
+                    ReadingStatus::Valid
+                } else {
+                    let since = saturated_since.unwrap_or(now);
+                    if now.saturating_sub(since) >= self.config.saturation_ms {
+                        self.start_heating(si7021, now)?;
+                    } else {
+                        self.state = State::Monitoring {
+                            saturated_since: Some(since),
+                        };
+                    }
+                    ReadingStatus::Saturated
+                }
+            }
+            State::Heating { .. } => ReadingStatus::Heating,
+            State::CoolingDown { .. } => ReadingStatus::CoolingDown,
+        };
+        Ok(RecoveryReading {
+            measurement,
+            status,
+        })
+    }
+
+    /// Stops a running heat-and-dry cycle, restoring the previous heater configuration
+    pub fn abort<E, I2C>(&mut self, si7021: &mut Si7021<I2C>) -> Result<(), Error<E>>
+    where
+        I2C: I2cBus<Error = E>,
+    {
+        if let State::Heating { previous, .. } = self.state {
+            si7021.apply(&previous)?;
+        }
+        self.state = State::Monitoring {
+            saturated_since: None,
+        };
+        Ok(())
+    }
+
+    fn start_heating<E, I2C>(&mut self, si7021: &mut Si7021<I2C>, now: u64) -> Result<(), Error<E>>
+    where
+        I2C: I2cBus<Error = E>,
+    {
+        let previous = si7021.current_config()?;
+        // Entered first, so a partly applied heater setting is still restored
+        self.state = State::Heating {
+            since: now,
+            previous,
+        };
+        si7021.set_heater(Some(self.config.heater_level))
+    }
+
+    // Moves on to the next phase once the current one has run for its configured time
+    fn finish_phase<E, I2C>(&mut self, si7021: &mut Si7021<I2C>, now: u64) -> Result<(), Error<E>>
+    where
+        I2C: I2cBus<Error = E>,
+    {
+        match self.state {
+            State::Heating { since, previous }
+                if now.saturating_sub(since) >= self.config.heating_ms =>
+            {
+                si7021.apply(&previous)?;
+                self.state = State::CoolingDown { since: now };
+            }
+            State::CoolingDown { since }
+                if now.saturating_sub(since) >= self.config.cooldown_ms =>
+            {
+                self.state = State::Monitoring {
+                    saturated_since: None,
+                };
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,442 @@
+// Typed views of the configuration registers, including the Si7013 User Register 2. Setters only
+// change their own field, reserved bits are kept as read from the sensor.
+
+use super::{ensure_supported, Chip, Error, HeaterLevel, I2cBus, MeasurementResolution, Si7021};
+use super::{
+    READ_HEATER_REGISTER, READ_USER_REGISTER1, WRITE_HEATER_REGISTER, WRITE_USER_REGISTER1,
+};
+
+// Measurement resolution bits RES1 (bit 7) and RES0 (bit 0)
+const RESOLUTION: u8 = 0x81;
+pub(crate) const VDDS: u8 = 0x40;
+const HTRE: u8 = 0x04;
+const HEATER: u8 = 0x0f;
+
+/// Supply voltage state reported by the VDDS bit of User Register 1
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VddStatus {
+    Ok,
+    // VDD dropped below the minimum operating voltage, measurements are unreliable
+    Low,
+}
+
+/// User Register 1, configuring resolution and heater
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UserRegister1 {
+    bits: u8,
+}
+
+impl Default for UserRegister1 {
+    // Power-on value 0x3A
+    fn default() -> Self {
+        UserRegister1 { bits: 0x3a }
+    }
+}
+
+impl UserRegister1 {
+    pub fn from_bits(bits: u8) -> Self {
+        UserRegister1 { bits }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub fn measurement_resolution(self) -> MeasurementResolution {
+        match self.bits & RESOLUTION {
+            0x00 => MeasurementResolution::Rh12Temp14,
+            0x01 => MeasurementResolution::Rh8Temp12,
+            0x80 => MeasurementResolution::Rh10Temp10,
+            // Use wildcard for 0x81 case, the compiler doesn't know all values have been covered
+            _ => MeasurementResolution::Rh11Temp11,
+        }
+    }
+
+    pub fn set_measurement_resolution(&mut self, measurement_resolution: MeasurementResolution) {
+        self.bits = (self.bits & !RESOLUTION) | measurement_resolution as u8
+    }
+
+    // Read-only, the sensor ignores the bit on writes
+    pub fn vdd_status(self) -> VddStatus {
+        if self.bits & VDDS != 0 {
+            VddStatus::Low
+        } else {
+            VddStatus::Ok
+        }
+    }
+
+    pub fn heater_enabled(self) -> bool {
+        self.bits & HTRE != 0
+    }
+
+    pub fn set_heater_enabled(&mut self, enabled: bool) {
+        self.bits = (self.bits & !HTRE) | (HTRE * u8::from(enabled))
+    }
+}
+
+/// Heater Control Register, selecting the heater current
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct HeaterControlRegister {
+    bits: u8,
+}
+
+impl HeaterControlRegister {
+    pub fn from_bits(bits: u8) -> Self {
+        HeaterControlRegister { bits }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    pub fn heater_level(self) -> HeaterLevel {
+        HeaterLevel::new(self.bits & HEATER).unwrap_or_default()
+    }
+
+    pub fn set_heater_level(&mut self, heater_level: HeaterLevel) {
+        self.bits = (self.bits & !HEATER) | heater_level.level()
+    }
+}
+
+/// Sensor configuration stored in User Register 1 and the Heater Control Register
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Config {
+    pub measurement_resolution: MeasurementResolution,
+    pub heater_enabled: bool,
+    // Kept while the heater is disabled, chips without a heater control register only support
+    // HeaterLevel::MIN
+    pub heater_level: HeaterLevel,
+}
+
+impl Default for Config {
+    // Power-on configuration
+    fn default() -> Self {
+        Config {
+            measurement_resolution: MeasurementResolution::Rh12Temp14,
+            heater_enabled: false,
+            heater_level: HeaterLevel::MIN,
+        }
+    }
+}
+
+impl Config {
+    pub fn with_measurement_resolution(
+        mut self,
+        measurement_resolution: MeasurementResolution,
+    ) -> Self {
+        self.measurement_resolution = measurement_resolution;
+        self
+    }
+
+    // Enables the heater at the given level, or disables it keeping the configured level
+    pub fn with_heater(mut self, heater: Option<HeaterLevel>) -> Self {
+        self.heater_enabled = heater.is_some();
+        self.heater_level = heater.unwrap_or(self.heater_level);
+        self
+    }
+
+    pub(crate) fn from_registers(
+        user_register: UserRegister1,
+        heater_register: HeaterControlRegister,
+    ) -> Self {
+        Config {
+            measurement_resolution: user_register.measurement_resolution(),
+            heater_enabled: user_register.heater_enabled(),
+            heater_level: heater_register.heater_level(),
+        }
+    }
+
+    // Register values for this configuration, keeping the other bits of the given registers
+    pub(crate) fn registers(
+        &self,
+        mut user_register: UserRegister1,
+        mut heater_register: HeaterControlRegister,
+    ) -> (UserRegister1, HeaterControlRegister) {
+        user_register.set_measurement_resolution(self.measurement_resolution);
+        user_register.set_heater_enabled(self.heater_enabled);
+        heater_register.set_heater_level(self.heater_level);
+        (user_register, heater_register)
+    }
+
+    // Registers to write for this configuration, None for those already holding it
+    pub(crate) fn changes(
+        &self,
+        chip: Chip,
+        user_register: UserRegister1,
+        heater_register: HeaterControlRegister,
+    ) -> (Option<UserRegister1>, Option<HeaterControlRegister>) {
+        let (new_user_register, new_heater_register) =
+            self.registers(user_register, heater_register);
+        (
+            Some(new_user_register).filter(|&register| register != user_register),
+            Some(new_heater_register)
+                .filter(|&register| chip.has_heater_register() && register != heater_register),
+        )
+    }
+
+    pub(crate) fn ensure_supported<E>(&self, chip: Chip) -> Result<(), Error<E>> {
+        if self.heater_enabled {
+            ensure_supported(chip.has_heater())?;
+        }
+        if chip.has_heater() && !chip.has_heater_register() && self.heater_level != HeaterLevel::MIN
+        {
+            return Err(Error::InvalidHeaterLevel);
+        }
+        Ok(())
+    }
+}
+
+// Register values switching the heater on at the given level or off, keeping the other bits
+pub(crate) fn heater_registers<E>(
+    chip: Chip,
+    heater_level: Option<HeaterLevel>,
+    mut user_register: UserRegister1,
+    mut heater_register: HeaterControlRegister,
+) -> Result<(UserRegister1, HeaterControlRegister), Error<E>> {
+    match heater_level {
+        Some(level) if !chip.has_heater_register() && level != HeaterLevel::MIN => {
+            return Err(Error::InvalidHeaterLevel)
+        }
+        Some(level) => {
+            heater_register.set_heater_level(level);
+            user_register.set_heater_enabled(true)
+        }
+        None => user_register.set_heater_enabled(false),
+    }
+    Ok((user_register, heater_register))
+}
+
+// Retries for apply() and apply_verified(), the latter verifies even without set_write_verify()
+pub(crate) fn apply_verify(write_verify: Option<u8>, verified: bool) -> Option<u8> {
+    if verified {
+        Some(write_verify.unwrap_or(0))
+    } else {
+        write_verify
+    }
+}
+
+// Compares a register read back after a write, ignoring read-only bits
+fn verify_register<E>(expected: u8, actual: u8, read_only: u8) -> Result<(), Error<E>> {
+    if expected & !read_only == actual & !read_only {
+        Ok(())
+    } else {
+        Err(Error::VerifyFailed { expected, actual })
+    }
+}
+
+// Progress of a register write, read back and rewritten up to the given number of retries if
+// verification is enabled
+pub(crate) struct RegisterWrite {
+    value: u8,
+    read_only: u8,
+    verify: bool,
+    retries: u8,
+}
+
+impl RegisterWrite {
+    pub(crate) fn new(value: u8, read_only: u8, verify: Option<u8>) -> Self {
+        RegisterWrite {
+            value,
+            read_only,
+            verify: verify.is_some(),
+            retries: verify.unwrap_or(0),
+        }
+    }
+
+    pub(crate) fn verify(&self) -> bool {
+        self.verify
+    }
+
+    // Checks the value read back, true once the write is done and false to write again. Fails
+    // with VerifyFailed when no retries are left.
+    pub(crate) fn check<E>(&mut self, actual: u8) -> Result<bool, Error<E>> {
+        match verify_register(self.value, actual, self.read_only) {
+            Ok(()) => Ok(true),
+            Err(e) if self.retries == 0 => Err(e),
+            Err(_) => {
+                self.retries -= 1;
+                Ok(false)
+            }
+        }
+    }
+}
+
+// Checks that User Register 1 holds its power-on value after a reset. HTU21D and SHT21 use other
+// reserved bits, so only resolution and heater are compared there.
+pub(crate) fn verify_reset<E>(chip: Chip, user_register: UserRegister1) -> Result<(), Error<E>> {
+    let ignored = if chip.has_status_bits() {
+        !(RESOLUTION | HTRE)
+    } else {
+        VDDS
+    };
+    verify_register(
+        UserRegister1::default().bits(),
+        user_register.bits(),
+        ignored,
+    )
+}
+
+/// Shadow copies of the configuration registers, None until read or written
+#[derive(Default)]
+pub struct RegisterCache {
+    pub user_register: Option<UserRegister1>,
+    pub heater_register: Option<HeaterControlRegister>,
+}
+
+impl RegisterCache {
+    // Register values after a reset of the sensor. HTU21D and SHT21 power up with other reserved
+    // bits than the Si7021, so their User Register 1 is read again on the next access.
+    pub fn after_reset(chip: Chip) -> Self {
+        RegisterCache {
+            user_register: if chip.has_status_bits() {
+                None
+            } else {
+                Some(UserRegister1::default())
+            },
+            heater_register: Some(HeaterControlRegister::default()),
+        }
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    pub fn read_user_register(&mut self) -> Result<UserRegister1, Error<E>> {
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_USER_REGISTER1, &mut buffer)?;
+        let user_register = UserRegister1::from_bits(buffer[0]);
+        self.measurement_resolution = user_register.measurement_resolution();
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.user_register = Some(user_register);
+        }
+        Ok(user_register)
+    }
+
+    // Like read_user_register(), but returns the cached value if available
+    pub(crate) fn cached_user_register(&mut self) -> Result<UserRegister1, Error<E>> {
+        match self
+            .register_cache
+            .as_ref()
+            .and_then(|cache| cache.user_register)
+        {
+            Some(user_register) => Ok(user_register),
+            None => self.read_user_register(),
+        }
+    }
+
+    pub fn write_user_register(&mut self, user_register: UserRegister1) -> Result<(), Error<E>> {
+        self.write_user_register_verify(user_register, self.write_verify)
+    }
+
+    fn write_user_register_verify(
+        &mut self,
+        user_register: UserRegister1,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        let result = self.write_register(
+            WRITE_USER_REGISTER1,
+            READ_USER_REGISTER1,
+            user_register.bits(),
+            VDDS,
+            verify,
+        );
+        // The register content is unknown after a failed write
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.user_register = result.is_ok().then_some(user_register);
+        }
+        result?;
+        self.measurement_resolution = user_register.measurement_resolution();
+        Ok(())
+    }
+
+    pub fn read_heater_register(&mut self) -> Result<HeaterControlRegister, Error<E>> {
+        ensure_supported(self.chip.has_heater_register())?;
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_HEATER_REGISTER, &mut buffer)?;
+        let heater_register = HeaterControlRegister::from_bits(buffer[0]);
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.heater_register = Some(heater_register);
+        }
+        Ok(heater_register)
+    }
+
+    // Like read_heater_register(), but returns the cached value if available
+    pub(crate) fn cached_heater_register(&mut self) -> Result<HeaterControlRegister, Error<E>> {
+        match self
+            .register_cache
+            .as_ref()
+            .and_then(|cache| cache.heater_register)
+        {
+            Some(heater_register) => Ok(heater_register),
+            None => self.read_heater_register(),
+        }
+    }
+
+    pub fn write_heater_register(
+        &mut self,
+        heater_register: HeaterControlRegister,
+    ) -> Result<(), Error<E>> {
+        self.write_heater_register_verify(heater_register, self.write_verify)
+    }
+
+    fn write_heater_register_verify(
+        &mut self,
+        heater_register: HeaterControlRegister,
+        verify: Option<u8>,
+    ) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_heater_register())?;
+        let result = self.write_register(
+            WRITE_HEATER_REGISTER,
+            READ_HEATER_REGISTER,
+            heater_register.bits(),
+            0,
+            verify,
+        );
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.heater_register = result.is_ok().then_some(heater_register);
+        }
+        result
+    }
+
+    // Chips without a heater control register behave like one with the lowest level
+    fn heater_register_or_default(&mut self) -> Result<HeaterControlRegister, Error<E>> {
+        if self.chip.has_heater_register() {
+            self.cached_heater_register()
+        } else {
+            Ok(HeaterControlRegister::default())
+        }
+    }
+
+    pub fn current_config(&mut self) -> Result<Config, Error<E>> {
+        let user_register = self.cached_user_register()?;
+        let heater_register = self.heater_register_or_default()?;
+        Ok(Config::from_registers(user_register, heater_register))
+    }
+
+    // Reads each register once and only writes those whose value changes
+    pub fn apply(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.apply_config(config, false)
+    }
+
+    // Like apply(), but verifies written registers also when set_write_verify() is not enabled
+    pub fn apply_verified(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.apply_config(config, true)
+    }
+
+    fn apply_config(&mut self, config: &Config, verified: bool) -> Result<(), Error<E>> {
+        config.ensure_supported(self.chip)?;
+        let verify = apply_verify(self.write_verify, verified);
+        let user_register = self.cached_user_register()?;
+        let heater_register = self.heater_register_or_default()?;
+        let (user_register, heater_register) =
+            config.changes(self.chip, user_register, heater_register);
+        if let Some(user_register) = user_register {
+            self.write_user_register_verify(user_register, verify)?;
+        }
+        if let Some(heater_register) = heater_register {
+            self.write_heater_register_verify(heater_register, verify)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use super::{
+    DelayNs, Error, HeaterLevel, I2cBus, Measurement, RelativeHumidity, Si7021, Temperature,
+};
+
+/// Parameters of the heater self-test
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelfTestConfig {
+    pub heater_level: HeaterLevel,
+    // Number of temperature readings taken while heating
+    pub samples: u8,
+    // Time between the heater being switched on and each reading
+    pub interval_ms: u32,
+    // Minimum increase of the peak temperature over the baseline for the test to pass
+    pub min_temperature_rise: Temperature,
+    // Minimum decrease of relative humidity at the end of heating for the test to pass
+    pub min_humidity_drop: RelativeHumidity,
+}
+
+impl Default for SelfTestConfig {
+    fn default() -> Self {
+        SelfTestConfig {
+            heater_level: HeaterLevel::MAX,
+            samples: 5,
+            interval_ms: 1_000,
+            min_temperature_rise: Temperature::from_centi_celsius(200),
+            min_humidity_drop: RelativeHumidity::from_centi_percent(100),
+        }
+    }
+}
+
+/// Outcome of [`Si7021::heater_self_test()`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    // Reading taken before the heater was switched on
+    pub baseline: Measurement,
+    // Highest temperature and final humidity while heating
+    pub heated: Measurement,
+    pub temperature_rise: Temperature,
+    pub humidity_drop: RelativeHumidity,
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    // Reads humidity and temperature with separate conversions, also supported by HTU21D
+    fn self_test_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        let humidity = self.measure_humidity()?;
+        let temperature = self.measure_temperature()?;
+        Ok(Measurement {
+            humidity,
+            temperature,
+        })
+    }
+
+    fn heat_and_sample<D: DelayNs>(
+        &mut self,
+        config: &SelfTestConfig,
+        delay: &mut D,
+    ) -> Result<Measurement, Error<E>> {
+        self.set_heater(Some(config.heater_level))?;
+        let mut peak = None;
+        for _ in 0..config.samples {
+            delay.delay_ms(config.interval_ms);
+            let temperature = self.measure_temperature()?;
+            peak = peak.max(Some(temperature));
+        }
+        let humidity = self.measure_humidity()?;
+        let temperature = self.measure_temperature()?;
+        Ok(Measurement {
+            humidity,
+            temperature: peak.map_or(temperature, |peak| peak.max(temperature)),
+        })
+    }
+
+    // Verifies that the sensor responds to its own heater, proving readings are not stale. The
+    // previous heater configuration is restored afterwards, also when a reading fails. Humidity
+    // cannot drop noticeably in very dry air, lower min_humidity_drop there.
+    pub fn heater_self_test<D: DelayNs>(
+        &mut self,
+        config: &SelfTestConfig,
+        delay: &mut D,
+    ) -> Result<SelfTestReport, Error<E>> {
+        let baseline = self.self_test_measurement()?;
+        let previous = self.current_config()?;
+        let heated = self.heat_and_sample(config, delay);
+        let restored = self.apply(&previous);
+        let heated = heated?;
+        restored?;
+        let temperature_rise = heated.temperature - baseline.temperature;
+        let humidity_drop = baseline.humidity - heated.humidity;
+        Ok(SelfTestReport {
+            passed: temperature_rise >= config.min_temperature_rise
+                && humidity_drop >= config.min_humidity_drop,
+            baseline,
+            heated,
+            temperature_rise,
+            humidity_drop,
+        })
+    }
+}
@@ -0,0 +1,206 @@
+use super::internal::{Analog, TemperatureBuffer};
+use super::{ensure_supported, Error, I2cBus, Si7021, Temperature};
+
+const MEASURE_ANALOG: &[u8] = &[0xee];
+const WRITE_USER_REGISTER2: &[u8] = &[0x50];
+const READ_USER_REGISTER2: &[u8] = &[0x10];
+
+const VOUT: u8 = 0x01;
+const VREFP: u8 = 0x02;
+const VIN_BUFFERED: u8 = 0x04;
+const THERMISTOR_CORRECTION: u8 = 0x20;
+const FAST_CONVERSION: u8 = 0x40;
+
+// Reference voltage of the internal VREFP source in µV
+const INTERNAL_REFERENCE_UV: i64 = 1_250_000;
+
+/// Level the VOUT pin is driven to, e.g. to power a thermistor bridge
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Vout {
+    Gnd,
+    Vddd,
+}
+
+/// Reference voltage for the analog input
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VoltageReference {
+    // Internal 1.25V reference
+    Internal,
+    Vdda,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConversionTime {
+    Normal,
+    Fast,
+}
+
+/// Si7013 User Register 2, configuring the analog input
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UserRegister2 {
+    bits: u8,
+}
+
+impl UserRegister2 {
+    pub fn from_bits(bits: u8) -> Self {
+        UserRegister2 { bits }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.bits
+    }
+
+    fn bit(self, mask: u8) -> bool {
+        self.bits & mask != 0
+    }
+
+    fn set_bit(&mut self, mask: u8, on: bool) {
+        self.bits = (self.bits & !mask) | (mask * u8::from(on))
+    }
+
+    pub fn vout(self) -> Vout {
+        if self.bit(VOUT) {
+            Vout::Vddd
+        } else {
+            Vout::Gnd
+        }
+    }
+
+    pub fn set_vout(&mut self, vout: Vout) {
+        self.set_bit(VOUT, vout == Vout::Vddd)
+    }
+
+    pub fn voltage_reference(self) -> VoltageReference {
+        if self.bit(VREFP) {
+            VoltageReference::Vdda
+        } else {
+            VoltageReference::Internal
+        }
+    }
+
+    pub fn set_voltage_reference(&mut self, voltage_reference: VoltageReference) {
+        self.set_bit(VREFP, voltage_reference == VoltageReference::Vdda)
+    }
+
+    pub fn vin_buffered(self) -> bool {
+        self.bit(VIN_BUFFERED)
+    }
+
+    pub fn set_vin_buffered(&mut self, buffered: bool) {
+        self.set_bit(VIN_BUFFERED, buffered)
+    }
+
+    pub fn thermistor_correction(self) -> bool {
+        self.bit(THERMISTOR_CORRECTION)
+    }
+
+    pub fn set_thermistor_correction(&mut self, enabled: bool) {
+        self.set_bit(THERMISTOR_CORRECTION, enabled)
+    }
+
+    pub fn conversion_time(self) -> ConversionTime {
+        if self.bit(FAST_CONVERSION) {
+            ConversionTime::Fast
+        } else {
+            ConversionTime::Normal
+        }
+    }
+
+    pub fn set_conversion_time(&mut self, conversion_time: ConversionTime) {
+        self.set_bit(FAST_CONVERSION, conversion_time == ConversionTime::Fast)
+    }
+}
+
+/// Result of an analog input measurement
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AnalogVoltage {
+    pub raw: i16,
+    pub reference: VoltageReference,
+}
+
+impl AnalogVoltage {
+    // Returns the input voltage in µV, vdda_uv is only used with the VDDA reference
+    pub fn microvolts(&self, vdda_uv: i32) -> i32 {
+        let reference_uv = match self.reference {
+            VoltageReference::Internal => INTERNAL_REFERENCE_UV,
+            VoltageReference::Vdda => i64::from(vdda_uv),
+        };
+        (i64::from(self.raw) * reference_uv / 32768) as i32
+    }
+}
+
+impl<E, I2C> Si7021<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    pub fn user_register2(&mut self) -> Result<UserRegister2, Error<E>> {
+        ensure_supported(self.chip.has_analog_input())?;
+        let mut buffer = [0u8; 1];
+        self.write_read(READ_USER_REGISTER2, &mut buffer)?;
+        Ok(UserRegister2::from_bits(buffer[0]))
+    }
+
+    pub fn set_user_register2(&mut self, user_register2: UserRegister2) -> Result<(), Error<E>> {
+        ensure_supported(self.chip.has_analog_input())?;
+        self.write_register(
+            WRITE_USER_REGISTER2,
+            READ_USER_REGISTER2,
+            user_register2.bits(),
+            0,
+            self.write_verify,
+        )
+    }
+
+    // Switches thermistor correction on or off, keeping the rest of User Register 2
+    fn configure_thermistor_correction(
+        &mut self,
+        enabled: bool,
+    ) -> Result<UserRegister2, Error<E>> {
+        let mut user_register2 = self.user_register2()?;
+        if user_register2.thermistor_correction() != enabled {
+            user_register2.set_thermistor_correction(enabled);
+            self.set_user_register2(user_register2)?;
+        }
+        Ok(user_register2)
+    }
+
+    // Measures the voltage on the VIN/VINP analog input. Thermistor correction is switched off
+    // for the measurement and enabled again afterwards if it was on.
+    pub fn analog_voltage(&mut self) -> Result<AnalogVoltage, Error<E>> {
+        let user_register2 = self.user_register2()?;
+        if !user_register2.thermistor_correction() {
+            return self.measure_analog(user_register2);
+        }
+        let mut uncorrected = user_register2;
+        uncorrected.set_thermistor_correction(false);
+        self.set_user_register2(uncorrected)?;
+        let voltage = self.measure_analog(uncorrected);
+        let restored = self.set_user_register2(user_register2);
+        let voltage = voltage?;
+        restored?;
+        Ok(voltage)
+    }
+
+    fn measure_analog(&mut self, user_register2: UserRegister2) -> Result<AnalogVoltage, Error<E>> {
+        let mut analog: Analog<E> = Analog::new();
+        self.write_read(MEASURE_ANALOG, analog.buf())?;
+        Ok(AnalogVoltage {
+            raw: analog.analog()?,
+            reference: user_register2.voltage_reference(),
+        })
+    }
+
+    // Uses the correction coefficients programmed into the sensor
+    pub fn measure_thermistor_temperature(&mut self) -> Result<Temperature, Error<E>> {
+        self.configure_thermistor_correction(true)?;
+        let mut temperature: TemperatureBuffer<E> = TemperatureBuffer::for_chip(self.chip);
+        self.write_read(MEASURE_ANALOG, temperature.buf())?;
+        temperature.temperature()
+    }
+
+    // Returns the thermistor temperature in °C scaled by 100, i.e. 23.15°C returns 2315
+    pub fn thermistor_temperature(&mut self) -> Result<i32, Error<E>> {
+        self.measure_thermistor_temperature()
+            .map(Temperature::centi_celsius)
+    }
+}
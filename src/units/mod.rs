@@ -0,0 +1,175 @@
+use core::fmt;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+// Formats a value scaled by 100 with two decimal places
+fn fmt_centi(value: i32, unit: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.unsigned_abs();
+    write!(f, "{}{}.{:02}{}", sign, value / 100, value % 100, unit)
+}
+
+/// Temperature in °C scaled by 100, i.e. 23.15°C is stored as 2315
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Temperature(i32);
+
+impl Temperature {
+    pub const fn from_centi_celsius(centi_celsius: i32) -> Self {
+        Temperature(centi_celsius)
+    }
+
+    /// Converts a temperature code as returned by the sensor
+    pub fn from_raw(code: u16) -> Self {
+        Temperature(17572 * i32::from(code) / 65536 - 4685)
+    }
+
+    pub const fn centi_celsius(self) -> i32 {
+        self.0
+    }
+
+    /// Converts a temperature in °F scaled by 100, rounding to the nearest centi-degree Celsius
+    pub const fn from_centi_fahrenheit(centi_fahrenheit: i32) -> Self {
+        let offset = centi_fahrenheit as i64 - 3200;
+        let half = if offset < 0 { -4 } else { 4 };
+        Temperature(((offset * 5 + half) / 9) as i32)
+    }
+
+    /// Converts to °F scaled by 100, rounding to the nearest centi-degree like
+    /// [`from_centi_fahrenheit`](Temperature::from_centi_fahrenheit)
+    pub const fn centi_fahrenheit(self) -> i32 {
+        let half = if self.0 < 0 { -2 } else { 2 };
+        (self.0 * 9 + half) / 5 + 3200
+    }
+
+    pub const fn centi_kelvin(self) -> i32 {
+        self.0 + 27315
+    }
+
+    /// Smallest sensor code converting to at least this temperature, saturating outside the
+    /// measurable range
+    pub fn raw(self) -> u16 {
+        let code = ((i64::from(self.0) + 4685) * 65536 + 17571).div_euclid(17572);
+        code.clamp(0, i64::from(u16::MAX)) as u16
+    }
+
+    #[cfg(feature = "float")]
+    pub fn celsius(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    #[cfg(feature = "float")]
+    pub fn fahrenheit(self) -> f32 {
+        self.celsius() * 1.8 + 32.0
+    }
+
+    #[cfg(feature = "float")]
+    pub fn kelvin(self) -> f32 {
+        self.celsius() + 273.15
+    }
+}
+
+impl Add for Temperature {
+    type Output = Temperature;
+
+    fn add(self, rhs: Temperature) -> Temperature {
+        Temperature(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Temperature {
+    fn add_assign(&mut self, rhs: Temperature) {
+        self.0 += rhs.0
+    }
+}
+
+impl Sub for Temperature {
+    type Output = Temperature;
+
+    fn sub(self, rhs: Temperature) -> Temperature {
+        Temperature(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Temperature {
+    fn sub_assign(&mut self, rhs: Temperature) {
+        self.0 -= rhs.0
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_centi(self.0, "°C", f)
+    }
+}
+
+/// Relative humidity in % scaled by 100, i.e. 23.15% is stored as 2315
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct RelativeHumidity(i32);
+
+impl RelativeHumidity {
+    pub const fn from_centi_percent(centi_percent: i32) -> Self {
+        RelativeHumidity(centi_percent)
+    }
+
+    /// Converts a humidity code as returned by the sensor, clamped to 0-100%
+    pub fn from_raw(code: u16) -> Self {
+        let centi_percent = 12500 * i32::from(code) / 65536 - 600;
+        RelativeHumidity(centi_percent.clamp(0, 10000))
+    }
+
+    pub const fn centi_percent(self) -> i32 {
+        self.0
+    }
+
+    /// Smallest sensor code converting to at least this humidity, saturating outside the
+    /// measurable range
+    pub fn raw(self) -> u16 {
+        let code = ((i64::from(self.0) + 600) * 65536 + 12499).div_euclid(12500);
+        code.clamp(0, i64::from(u16::MAX)) as u16
+    }
+
+    #[cfg(feature = "float")]
+    pub fn percent(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+}
+
+impl Add for RelativeHumidity {
+    type Output = RelativeHumidity;
+
+    fn add(self, rhs: RelativeHumidity) -> RelativeHumidity {
+        RelativeHumidity(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for RelativeHumidity {
+    fn add_assign(&mut self, rhs: RelativeHumidity) {
+        self.0 += rhs.0
+    }
+}
+
+impl Sub for RelativeHumidity {
+    type Output = RelativeHumidity;
+
+    fn sub(self, rhs: RelativeHumidity) -> RelativeHumidity {
+        RelativeHumidity(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for RelativeHumidity {
+    fn sub_assign(&mut self, rhs: RelativeHumidity) {
+        self.0 -= rhs.0
+    }
+}
+
+impl fmt::Display for RelativeHumidity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_centi(self.0, "%RH", f)
+    }
+}
+
+/// Relative humidity and the temperature measured during the same conversion
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Measurement {
+    pub humidity: RelativeHumidity,
+    pub temperature: Temperature,
+}
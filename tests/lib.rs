@@ -1,85 +1,973 @@
-#[cfg(test)]
-mod tests {
-    use embedded_hal_mock::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
-    use embedded_hal_mock::MockError;
-    use si7021_hal::MeasurementResolution;
-    use si7021_hal::Si7021;
-    use std::io::ErrorKind;
+use si7021_hal::Si7021;
+use si7021_hal::{AnalogVoltage, ConversionTime, UserRegister2, VoltageReference, Vout};
+use si7021_hal::{Chip, DeviceId, Error, FirmwareRevision, HeaterLevel, Model, SlaveAddr};
+use si7021_hal::{Config, HeaterControlRegister, MeasurementResolution, UserRegister1, VddStatus};
+use si7021_hal::{Measurement, RelativeHumidity, Temperature};
 
-    #[test]
-    fn get_temperature() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0xe3],
-            vec![0x66, 0x4c, 0x4f],
-        )]));
+// Shorthands for readings in centi-units, used by the psychrometrics and comfort tests
+fn celsius(centi_celsius: i32) -> Temperature {
+    Temperature::from_centi_celsius(centi_celsius)
+}
+
+fn percent(centi_percent: i32) -> RelativeHumidity {
+    RelativeHumidity::from_centi_percent(centi_percent)
+}
+
+// The suite is run once per supported embedded-hal flavour, see the modules at the bottom
+macro_rules! si7021_tests {
+    () => {
+        #[test]
+        fn get_temperature() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0xe3],
+                vec![0x66, 0x4c, 0x4f],
+            )]);
+
+            let temperature = si7021.temperature();
+            assert!(temperature.is_ok());
+            assert_eq!(temperature.unwrap(), 2336);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_temperature_crc_failure() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0xe3],
+                vec![0x66, 0x4c, 0xff],
+            )]);
+
+            let temperature = si7021.temperature();
+            assert!(temperature.is_err());
+            assert_eq!(temperature.unwrap_err(), si7021_hal::Error::ChecksumFailure);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_humdity_and_temperature() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+            ]);
+
+            let humidity = si7021.humidity();
+            assert!(humidity.is_ok());
+            assert_eq!(humidity.unwrap(), 7292);
+
+            let temperature = si7021.temperature_rh_measurement();
+            assert!(temperature.is_ok());
+            assert_eq!(temperature.unwrap(), 2334);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn measure_humidity_and_temperature() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+            ]);
+
+            assert_eq!(
+                si7021.measure(),
+                Ok(Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(7292),
+                    temperature: Temperature::from_centi_celsius(2334),
+                })
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn measure_humidity_crc_failure() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0xe5],
+                vec![0xa1, 0xa6, 0xff],
+            )]);
+
+            assert_eq!(si7021.measure(), Err(Error::ChecksumFailure));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_humdity_crc_failure() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0xe5],
+                vec![0xa1, 0xa6, 0xff],
+            )]);
+
+            let humidity = si7021.humidity();
+            assert!(humidity.is_err());
+            assert_eq!(humidity.unwrap_err(), si7021_hal::Error::ChecksumFailure);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_humidity_temperature_without_humidity_measurement() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0xe0],
+                vec![0x00, 0x00],
+            )]);
+
+            let temperature = si7021.temperature_rh_measurement();
+            assert!(temperature.is_err());
+            assert_eq!(
+                temperature.unwrap_err(),
+                si7021_hal::Error::NoPreviousHumidityMeasurement
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_serial_number() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfa, 0x0f],
+                    vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
+                ),
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfc, 0xc9],
+                    vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+                ),
+            ]);
+
+            let serial_number = si7021.serial_number();
+            assert!(serial_number.is_ok());
+            assert_eq!(serial_number.unwrap(), 0x842cf9b115ffffff);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_serial_number_crc_failure_id1() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfa, 0x0f],
+                    vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xff],
+                ),
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfc, 0xc9],
+                    vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+                ),
+            ]);
+
+            let serial_number = si7021.serial_number();
+            assert!(serial_number.is_err());
+            assert_eq!(
+                serial_number.unwrap_err(),
+                si7021_hal::Error::ChecksumFailure
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_serial_number_crc_failure_id2() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfa, 0x0f],
+                    vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
+                ),
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfc, 0xc9],
+                    vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xff],
+                ),
+            ]);
+
+            let serial_number = si7021.serial_number();
+            assert!(serial_number.is_err());
+            assert_eq!(
+                serial_number.unwrap_err(),
+                si7021_hal::Error::ChecksumFailure
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_device_identity() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfa, 0x0f],
+                    vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
+                ),
+                I2cTransaction::write_read(
+                    0x40,
+                    vec![0xfc, 0xc9],
+                    vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+                ),
+                I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x20]),
+            ]);
+
+            let device_identity = si7021.device_identity();
+            assert_eq!(
+                device_identity,
+                Ok(DeviceId {
+                    serial: 0x842cf9b115ffffff,
+                    model: Model::Chip(Chip::Si7021),
+                    firmware: Some(FirmwareRevision::V2_0),
+                })
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_firmware_revision() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write_read(
+                0x40,
+                vec![0x84, 0xb8],
+                vec![0x20],
+            )]);
+
+            let firmware_revision = si7021.firmware_revision();
+            assert!(firmware_revision.is_ok());
+            assert_eq!(firmware_revision.unwrap(), 0x20);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_firmware_revision_i2c_error() {
+            let (mut si7021, mut i2c) =
+                new_si7021(&[
+                    I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x20])
+                        .with_error(mock_error()),
+                ]);
+
+            let firmware_revision = si7021.firmware_revision();
+            assert!(firmware_revision.is_err());
+            assert_eq!(
+                firmware_revision.unwrap_err(),
+                si7021_hal::Error::I2c(mock_error())
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn reset() {
+            let (mut si7021, mut i2c) = new_si7021(&[I2cTransaction::write(0x40, vec![0xfe])]);
+
+            let reset = si7021.reset();
+            assert!(reset.is_ok());
+
+            i2c.done();
+        }
+
+        #[test]
+        fn set_measurement_resolution() {
+            // Fill reserved bits with 1 and ensure they're written back
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0xff]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x7e]),
+            ]);
+
+            let measurement_resolution =
+                si7021.set_measurement_resolution(MeasurementResolution::Rh12Temp14);
+            assert!(measurement_resolution.is_ok());
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_measurement_resolution() {
+            let (mut si7021, mut i2c) =
+                new_si7021(&[I2cTransaction::write_read(0x40, vec![0xe7], vec![0x01])]);
+
+            let measurement_resolution = si7021.measurement_resolution();
+            assert!(measurement_resolution.is_ok());
+            assert_eq!(
+                measurement_resolution.unwrap(),
+                MeasurementResolution::Rh8Temp12
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_supply_status() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x7a]),
+            ]);
+
+            assert_eq!(si7021.supply_ok(), Ok(true));
+            assert_eq!(si7021.vdd_status(), Ok(VddStatus::Low));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn supply_check_low_voltage() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe3], vec![0x66, 0x4c, 0x4f]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x7a]),
+            ]);
+            si7021.set_supply_check(true);
+
+            assert_eq!(si7021.temperature(), Ok(2336));
+            assert_eq!(si7021.humidity(), Err(Error::LowSupplyVoltage));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_current_config() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3f]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x05]),
+            ]);
+
+            assert_eq!(
+                si7021.current_config(),
+                Ok(Config {
+                    measurement_resolution: MeasurementResolution::Rh8Temp12,
+                    heater_enabled: true,
+                    heater_level: HeaterLevel::new(5).unwrap(),
+                })
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn apply_config() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3f]),
+                I2cTransaction::write(0x40, vec![0x51, 0x05]),
+            ]);
+            let config = Config::default()
+                .with_measurement_resolution(MeasurementResolution::Rh8Temp12)
+                .with_heater(HeaterLevel::new(5));
+
+            assert_eq!(si7021.apply(&config), Ok(()));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn apply_config_writes_only_changed_registers() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+                I2cTransaction::write(0x40, vec![0x51, 0x03]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x03]),
+            ]);
+            let config = Config {
+                heater_level: HeaterLevel::new(3).unwrap(),
+                ..Config::default()
+            };
+
+            assert_eq!(si7021.apply(&config), Ok(()));
+            assert_eq!(si7021.apply(&config), Ok(()));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn apply_verified_config() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+                // VDDS is read-only and not compared
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x7b]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3b]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3a]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3b]),
+            ]);
+            let config =
+                Config::default().with_measurement_resolution(MeasurementResolution::Rh8Temp12);
+
+            assert_eq!(si7021.apply_verified(&config), Ok(()));
+            assert_eq!(
+                si7021.apply_verified(&Config::default()),
+                Err(Error::VerifyFailed {
+                    expected: 0x3a,
+                    actual: 0x3b
+                })
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn write_verify_retries_lost_write() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3b]),
+            ]);
+            si7021.set_write_verify(Some(1));
+
+            assert_eq!(
+                si7021.set_measurement_resolution(MeasurementResolution::Rh8Temp12),
+                Ok(())
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn write_verify_failed() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+                I2cTransaction::write(0x40, vec![0x51, 0x0a]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            ]);
+            si7021.set_write_verify(Some(0));
+
+            assert_eq!(
+                si7021.set_heater(HeaterLevel::new(0x0a)),
+                Err(Error::VerifyFailed {
+                    expected: 0x0a,
+                    actual: 0x00
+                })
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_heater_off() {
+            let (mut si7021, mut i2c) =
+                new_si7021(&[I2cTransaction::write_read(0x40, vec![0xe7], vec![0x11])]);
+
+            let heater = si7021.heater();
+            assert!(heater.is_ok());
+            assert_eq!(heater.unwrap(), None);
+
+            i2c.done();
+        }
+
+        #[test]
+        fn get_heater_on() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x04]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0x0a]),
+            ]);
+
+            let heater = si7021.heater();
+            assert!(heater.is_ok());
+            assert_eq!(heater.unwrap(), HeaterLevel::new(0x0a));
+
+            i2c.done();
+        }
+
+        #[test]
+        fn set_heater_off() {
+            // Fill reserved bits with 1 and ensure they're written back
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0xff]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0xff]),
+                I2cTransaction::write(0x40, vec![0xe6, 0xfb]),
+                I2cTransaction::write(0x40, vec![0x51, 0xff]),
+            ]);
+
+            let heater = si7021.set_heater(None);
+            assert!(heater.is_ok());
+
+            i2c.done();
+        }
+
+        #[test]
+        fn set_heater_on() {
+            // Fill reserved bits with 1 and ensure they're written back
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0xfb]),
+                I2cTransaction::write_read(0x40, vec![0x11], vec![0xf0]),
+                I2cTransaction::write(0x40, vec![0xe6, 0xff]),
+                I2cTransaction::write(0x40, vec![0x51, 0xfa]),
+            ]);
+
+            let heater = si7021.set_heater(HeaterLevel::new(0x0a));
+            assert!(heater.is_ok());
+
+            i2c.done();
+        }
+    };
+}
+
+// Tests for the blocking driver only, run against both embedded-hal flavours
+macro_rules! blocking_tests {
+    () => {
+        use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+        #[test]
+        fn measure_no_hold() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf5]),
+                I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(22_800)]);
+
+            assert_eq!(
+                si7021.measure_no_hold(&mut delay),
+                Ok(Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(7292),
+                    temperature: Temperature::from_centi_celsius(2334),
+                })
+            );
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn humidity_reading_no_hold() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf5]),
+                I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(22_800)]);
+
+            let reading = si7021.humidity_reading_no_hold(&mut delay);
+            assert_eq!(
+                reading.unwrap().measurement(),
+                Ok(Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(7292),
+                    temperature: Temperature::from_centi_celsius(2334),
+                })
+            );
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn supply_check_no_hold() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf3]),
+                I2cTransaction::read(0x40, vec![0x66, 0x4c, 0x4f]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x7a]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(10_800)]);
+            si7021.set_supply_check(true);
+
+            assert_eq!(
+                si7021.temperature_no_hold(&mut delay),
+                Err(Error::LowSupplyVoltage)
+            );
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn reset_with_delay_not_default() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xfe]),
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3b]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(15_000)]);
+
+            assert_eq!(
+                si7021.reset_with_delay(&mut delay),
+                Err(Error::VerifyFailed {
+                    expected: 0x3a,
+                    actual: 0x3b
+                })
+            );
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn get_temperature_no_hold_configured_resolution() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+                I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+                I2cTransaction::write(0x40, vec![0xf3]),
+                I2cTransaction::read(0x40, vec![0x66, 0x4c, 0x4f]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(3_800)]);
+
+            assert!(si7021
+                .set_measurement_resolution(MeasurementResolution::Rh8Temp12)
+                .is_ok());
+            let temperature = si7021.temperature_no_hold(&mut delay);
+            assert!(temperature.is_ok());
+            assert_eq!(temperature.unwrap(), 2336);
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn get_temperature_no_hold_crc_failure() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf3]),
+                I2cTransaction::read(0x40, vec![0x66, 0x4c, 0xff]),
+            ]);
+            let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(10_800)]);
+
+            let temperature = si7021.temperature_no_hold(&mut delay);
+            assert!(temperature.is_err());
+            assert_eq!(temperature.unwrap_err(), si7021_hal::Error::ChecksumFailure);
+
+            i2c.done();
+            delay.done();
+        }
+
+        #[test]
+        fn start_and_read_typed_measurements() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf5]),
+                I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+                I2cTransaction::write(0x40, vec![0xf3]),
+                I2cTransaction::read(0x40, vec![0x66, 0x4c, 0x4f]),
+            ]);
+
+            assert!(si7021.start_humidity_measurement().is_ok());
+            assert_eq!(
+                si7021.read_humidity_measurement(),
+                Ok(RelativeHumidity::from_centi_percent(7292))
+            );
+            assert!(si7021.start_temperature_measurement().is_ok());
+            assert_eq!(
+                si7021.read_temperature_measurement(),
+                Ok(Temperature::from_centi_celsius(2336))
+            );
+
+            i2c.done();
+        }
+
+        #[test]
+        fn start_and_read_humidity_crc_failure() {
+            let (mut si7021, mut i2c) = new_si7021(&[
+                I2cTransaction::write(0x40, vec![0xf5]),
+                I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0xff]),
+            ]);
+
+            assert!(si7021.start_humidity_measurement().is_ok());
+            assert_eq!(
+                si7021.read_humidity(),
+                Err(nb::Error::Other(si7021_hal::Error::ChecksumFailure))
+            );
+
+            i2c.done();
+        }
+    };
+}
+
+mod eh1 {
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource, Operation};
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    use super::*;
 
-        let temperature = si7021.temperature();
-        assert!(temperature.is_ok());
-        assert_eq!(temperature.unwrap(), 2336);
+    fn new_si7021(expectations: &[I2cTransaction]) -> (Si7021<I2cMock>, I2cMock) {
+        let i2c = I2cMock::new(expectations);
+        (Si7021::new(i2c.clone()), i2c)
     }
 
-    #[test]
-    fn get_temperature_crc_failure() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0xe3],
-            vec![0x66, 0x4c, 0xff],
-        )]));
+    fn mock_error() -> ErrorKind {
+        ErrorKind::Other
+    }
 
-        let temperature = si7021.temperature();
-        assert!(temperature.is_err());
-        assert_eq!(temperature.unwrap_err(), si7021_hal::Error::ChecksumFailure);
+    fn nack_error() -> ErrorKind {
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)
     }
 
+    si7021_tests!();
+    blocking_tests!();
+
     #[test]
-    fn get_humdity_and_temperature() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
-            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
-        ]));
+    fn get_humidity_no_hold() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(nack_error()),
+            I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+        ]);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(22_800),
+            DelayTransaction::delay_us(1_000),
+        ]);
 
-        let humidity = si7021.humidity();
+        let humidity = si7021.humidity_no_hold(&mut delay);
         assert!(humidity.is_ok());
         assert_eq!(humidity.unwrap(), 7292);
 
-        let temperature = si7021.temperature_rh_measurement();
-        assert!(temperature.is_ok());
-        assert_eq!(temperature.unwrap(), 2334);
+        i2c.done();
+        delay.done();
     }
 
     #[test]
-    fn get_humdity_crc_failure() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0xe5],
-            vec![0xa1, 0xa6, 0xff],
-        )]));
+    fn reset_with_delay() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xfe]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x00]).with_error(nack_error()),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+        ]);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(15_000),
+            DelayTransaction::delay_us(1_000),
+        ]);
+
+        assert_eq!(si7021.reset_with_delay(&mut delay), Ok(()));
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn get_humidity_no_hold_timeout() {
+        let mut expectations = vec![I2cTransaction::write(0x40, vec![0xf5])];
+        let mut delays = vec![DelayTransaction::delay_us(22_800)];
+        for _ in 0..10 {
+            expectations
+                .push(I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(nack_error()));
+            delays.push(DelayTransaction::delay_us(1_000));
+        }
+        expectations
+            .push(I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(nack_error()));
+        let (mut si7021, mut i2c) = new_si7021(&expectations);
+        let mut delay = CheckedDelay::new(&delays);
+
+        let humidity = si7021.humidity_no_hold(&mut delay);
+        assert!(humidity.is_err());
+        assert_eq!(humidity.unwrap_err(), si7021_hal::Error::Timeout);
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn start_and_read_humidity() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(nack_error()),
+            I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+        ]);
+
+        assert!(si7021.start_humidity_measurement().is_ok());
+        assert_eq!(si7021.read_humidity(), Err(nb::Error::WouldBlock));
+        assert_eq!(si7021.read_humidity(), Ok(7292));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn start_and_read_temperature() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf3]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(nack_error()),
+            I2cTransaction::read(0x40, vec![0x66, 0x4c, 0x4f]),
+        ]);
+
+        assert!(si7021.start_temperature_measurement().is_ok());
+        assert_eq!(si7021.read_temperature(), Err(nb::Error::WouldBlock));
+        assert_eq!(si7021.read_temperature(), Ok(2336));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn get_humidity_no_hold_bus_error() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(ErrorKind::Bus),
+        ]);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(22_800)]);
 
-        let humidity = si7021.humidity();
+        let humidity = si7021.humidity_no_hold(&mut delay);
         assert!(humidity.is_err());
-        assert_eq!(humidity.unwrap_err(), si7021_hal::Error::ChecksumFailure);
+        assert_eq!(
+            humidity.unwrap_err(),
+            si7021_hal::Error::I2c(ErrorKind::Bus)
+        );
+
+        i2c.done();
+        delay.done();
+    }
+
+    // Bus without NACK detection, every transfer fails
+    struct FaultyBus;
+
+    impl si7021_hal::I2cBus for FaultyBus {
+        type Error = ();
+
+        fn write(&mut self, _address: u8, _bytes: &[u8]) -> Result<(), ()> {
+            Err(())
+        }
+
+        fn write_read(
+            &mut self,
+            _address: u8,
+            _bytes: &[u8],
+            _buffer: &mut [u8],
+        ) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    impl si7021_hal::I2cRead for FaultyBus {
+        fn read(&mut self, _address: u8, _buffer: &mut [u8]) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn transaction() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::transaction_start(0x40),
+            I2cTransaction::write(0x40, vec![0x84, 0xb8]),
+            I2cTransaction::read(0x40, vec![0x20]),
+            I2cTransaction::transaction_end(0x40),
+        ]);
+        let mut firmware = [0u8; 1];
+
+        assert_eq!(
+            si7021.transaction(&mut [
+                Operation::Write(&[0x84, 0xb8]),
+                Operation::Read(&mut firmware)
+            ]),
+            Ok(())
+        );
+        assert_eq!(firmware, [0x20]);
+
+        i2c.done();
+    }
+
+    #[test]
+    fn custom_bus_errors_are_not_nack() {
+        let mut si7021 = Si7021::new(FaultyBus);
+
+        assert_eq!(
+            si7021.read_humidity(),
+            Err(nb::Error::Other(si7021_hal::Error::I2c(())))
+        );
     }
 
     #[test]
-    fn get_humidity_temperature_without_humidity_measurement() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
+    fn htu21d_humidity_ignores_status_bits() {
+        let mut i2c = I2cMock::new(&[I2cTransaction::write_read(
+            0x40,
+            vec![0xe5],
+            vec![0x7c, 0x02, 0xed],
+        )]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
+        let (mut si7021, mut si7021_i2c) = new_si7021(&[I2cTransaction::write_read(
             0x40,
-            vec![0xe0],
-            vec![0x00, 0x00],
-        )]));
+            vec![0xe5],
+            vec![0x7c, 0x02, 0xed],
+        )]);
+
+        assert_eq!(htu21d.humidity(), Ok(5454));
+        assert_eq!(si7021.humidity(), Ok(5455));
+
+        i2c.done();
+        si7021_i2c.done();
+    }
+
+    #[test]
+    fn si7050_humidity_unsupported() {
+        let mut i2c = I2cMock::new(&[]);
+        let mut si7050 = Si7021::new_with_chip(i2c.clone(), Chip::Si7050, SlaveAddr::Default);
+
+        assert_eq!(si7050.humidity(), Err(Error::UnsupportedCommand));
+        assert_eq!(
+            si7050.start_humidity_measurement(),
+            Err(Error::UnsupportedCommand)
+        );
+        assert_eq!(si7050.heater(), Err(Error::UnsupportedCommand));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn htu21d_unsupported_commands() {
+        let mut i2c = I2cMock::new(&[]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
 
-        let temperature = si7021.temperature_rh_measurement();
-        assert!(temperature.is_err());
         assert_eq!(
-            temperature.unwrap_err(),
-            si7021_hal::Error::NoPreviousHumidityMeasurement
+            htu21d.temperature_rh_measurement(),
+            Err(Error::UnsupportedCommand)
         );
+        assert_eq!(htu21d.firmware_revision(), Err(Error::UnsupportedCommand));
+        assert_eq!(htu21d.measure(), Err(Error::UnsupportedCommand));
+        assert!(matches!(
+            htu21d.humidity_reading(),
+            Err(Error::UnsupportedCommand)
+        ));
+
+        i2c.done();
     }
 
     #[test]
-    fn get_serial_number() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[
+    fn htu21d_heater_without_heater_register() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x02]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x06]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x06]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x06]),
+        ]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
+
+        assert_eq!(htu21d.set_heater(Some(HeaterLevel::MIN)), Ok(()));
+        assert_eq!(htu21d.heater(), Ok(Some(HeaterLevel::MIN)));
+        assert_eq!(
+            htu21d.set_heater(HeaterLevel::new(1)),
+            Err(Error::InvalidHeaterLevel)
+        );
+        assert_eq!(
+            htu21d.apply(&Config::default().with_heater(HeaterLevel::new(1))),
+            Err(Error::InvalidHeaterLevel)
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn sht21_humidity_no_hold_conversion_time() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0xa1, 0xa6, 0x51]),
+        ]);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(29_000)]);
+        let mut sht21 = Si7021::new_with_chip(i2c.clone(), Chip::Sht21, SlaveAddr::Default);
+
+        assert_eq!(sht21.humidity_no_hold(&mut delay), Ok(7292));
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn probe_si7013() {
+        let mut i2c = I2cMock::new(&[
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfa, 0x0f],
@@ -88,41 +976,47 @@ mod tests {
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfc, 0xc9],
-                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+                vec![0x0d, 0xff, 0xec, 0xff, 0xff, 0xef],
             ),
-        ]));
+            I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0xff]),
+        ]);
 
-        let serial_number = si7021.serial_number();
-        assert!(serial_number.is_ok());
-        assert_eq!(serial_number.unwrap(), 0x842cf9b115ffffff);
+        let si7021 = Si7021::probe(i2c.clone(), SlaveAddr::Default);
+        assert!(si7021.is_ok());
+        assert_eq!(si7021.unwrap().chip(), Chip::Si7013);
+
+        i2c.done();
     }
 
     #[test]
-    fn get_serial_number_crc_failure_id1() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[
+    fn probe_engineering_sample() {
+        let mut i2c = I2cMock::new(&[
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfa, 0x0f],
-                vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xff],
+                vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
             ),
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfc, 0xc9],
-                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+                vec![0x00, 0xff, 0xac, 0xff, 0xff, 0x66],
             ),
-        ]));
+            I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x20]),
+        ]);
 
-        let serial_number = si7021.serial_number();
-        assert!(serial_number.is_err());
+        let si7021 = Si7021::probe(i2c.clone(), SlaveAddr::Default);
+        assert!(si7021.is_err());
         assert_eq!(
-            serial_number.unwrap_err(),
-            si7021_hal::Error::ChecksumFailure
+            si7021.err().unwrap(),
+            Error::UnsupportedDevice(Model::EngineeringSample)
         );
+
+        i2c.done();
     }
 
     #[test]
-    fn get_serial_number_crc_failure_id2() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[
+    fn device_identity_without_firmware_revision() {
+        let (mut si7021, mut i2c) = new_si7021(&[
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfa, 0x0f],
@@ -131,148 +1025,1608 @@ mod tests {
             I2cTransaction::write_read(
                 0x40,
                 vec![0xfc, 0xc9],
-                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xff],
+                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
             ),
-        ]));
+            I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+        ]);
 
-        let serial_number = si7021.serial_number();
-        assert!(serial_number.is_err());
         assert_eq!(
-            serial_number.unwrap_err(),
-            si7021_hal::Error::ChecksumFailure
+            si7021.device_identity(),
+            Ok(DeviceId {
+                serial: 0x842cf9b115ffffff,
+                model: Model::Chip(Chip::Si7021),
+                firmware: None,
+            })
         );
-    }
-
-    #[test]
-    fn get_firmware_revision() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0x84, 0xb8],
-            vec![0x20],
-        )]));
 
-        let firmware_revision = si7021.firmware_revision();
-        assert!(firmware_revision.is_ok());
-        assert_eq!(firmware_revision.unwrap(), 0x20);
+        i2c.done();
     }
 
     #[test]
-    fn get_firmware_revision_i2c_error() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0x84, 0xb8],
-            vec![0x20],
-        )
-        .with_error(MockError::Io(ErrorKind::Other))]));
+    fn probe_htu21d() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(
+                0x40,
+                vec![0xfa, 0x0f],
+                vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
+            ),
+            I2cTransaction::write_read(
+                0x40,
+                vec![0xfc, 0xc9],
+                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+            ),
+            I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+        ]);
 
-        let firmware_revision = si7021.firmware_revision();
-        assert!(firmware_revision.is_err());
+        let si7021 = Si7021::probe(i2c.clone(), SlaveAddr::Default);
         assert_eq!(
-            firmware_revision.unwrap_err(),
-            si7021_hal::Error::I2c(MockError::Io(ErrorKind::Other))
+            si7021.err().unwrap(),
+            Error::UnsupportedDevice(Model::Chip(Chip::Si7021))
         );
+
+        i2c.done();
     }
 
     #[test]
-    fn reset() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write(0x40, vec![0xfe])]));
+    fn si7013_alternative_address() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x41, vec![0xe3], vec![0x66, 0x4c, 0x4f]),
+            I2cTransaction::write(0x41, vec![0xf5]),
+            I2cTransaction::read(0x41, vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write(0x41, vec![0xfe]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Alternative);
+
+        assert_eq!(si7013.temperature(), Ok(2336));
+        assert!(si7013.start_humidity_measurement().is_ok());
+        assert_eq!(si7013.read_humidity(), Ok(7292));
+        assert_eq!(si7013.reset(), Ok(()));
 
-        let reset = si7021.reset();
-        assert!(reset.is_ok());
+        i2c.done();
     }
 
     #[test]
-    fn set_measurement_resolution() {
-        // Fill reserved bits with 1 and ensure they're written back
-        let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe7], vec![0xff]),
-            I2cTransaction::write(0x40, vec![0xe6, 0x7e]),
-        ]));
+    fn custom_address() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x45, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write(0x45, vec![0xe6, 0x3b]),
+        ]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Custom(0x45));
 
-        let measurement_resolution =
-            si7021.set_measurement_resolution(MeasurementResolution::Rh12Temp14);
-        assert!(measurement_resolution.is_ok());
+        assert_eq!(
+            htu21d.set_measurement_resolution(MeasurementResolution::Rh8Temp12),
+            Ok(())
+        );
+
+        i2c.done();
     }
 
     #[test]
-    fn get_measurement_resolution() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0xe7],
-            vec![0x01],
-        )]));
+    fn si7013_user_register2() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0x85]),
+            I2cTransaction::write(0x40, vec![0x50, 0xc6]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Default);
 
-        let measurement_resolution = si7021.measurement_resolution();
-        assert!(measurement_resolution.is_ok());
+        let user_register2 = si7013.user_register2();
+        assert!(user_register2.is_ok());
+        let mut user_register2 = user_register2.unwrap();
+        assert_eq!(user_register2.vout(), Vout::Vddd);
         assert_eq!(
-            measurement_resolution.unwrap(),
-            MeasurementResolution::Rh8Temp12
+            user_register2.voltage_reference(),
+            VoltageReference::Internal
         );
+        assert!(user_register2.vin_buffered());
+        assert!(!user_register2.thermistor_correction());
+        assert_eq!(user_register2.conversion_time(), ConversionTime::Normal);
+
+        // Reserved bit 7 is written back unchanged
+        user_register2.set_vout(Vout::Gnd);
+        user_register2.set_voltage_reference(VoltageReference::Vdda);
+        user_register2.set_conversion_time(ConversionTime::Fast);
+        assert_eq!(user_register2, UserRegister2::from_bits(0xc6));
+        assert_eq!(si7013.set_user_register2(user_register2), Ok(()));
+
+        i2c.done();
     }
 
     #[test]
-    fn get_heater_off() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write_read(
-            0x40,
-            vec![0xe7],
-            vec![0x11],
-        )]));
+    fn si7013_user_register2_write_verify() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write(0x40, vec![0x50, 0x49]),
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0x48]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Default);
+        si7013.set_write_verify(Some(0));
+
+        assert_eq!(
+            si7013.set_user_register2(UserRegister2::from_bits(0x49)),
+            Err(Error::VerifyFailed {
+                expected: 0x49,
+                actual: 0x48
+            })
+        );
 
-        let heater = si7021.heater();
-        assert!(heater.is_ok());
-        assert_eq!(heater.unwrap(), None);
+        i2c.done();
     }
 
     #[test]
-    fn get_heater_on() {
-        let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x04]),
-            I2cTransaction::write_read(0x40, vec![0x11], vec![0x0a]),
-        ]));
+    fn si7013_analog_voltage() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0x00]),
+            I2cTransaction::write_read(0x40, vec![0xee], vec![0x40, 0x00, 0x89]),
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0x22]),
+            I2cTransaction::write(0x40, vec![0x50, 0x02]),
+            I2cTransaction::write_read(0x40, vec![0xee], vec![0xc0, 0x00, 0xaa]),
+            I2cTransaction::write(0x40, vec![0x50, 0x22]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Default);
+
+        let voltage = si7013.analog_voltage();
+        assert_eq!(
+            voltage,
+            Ok(AnalogVoltage {
+                raw: 0x4000,
+                reference: VoltageReference::Internal
+            })
+        );
+        assert_eq!(voltage.unwrap().microvolts(3_300_000), 625_000);
 
-        let heater = si7021.heater();
-        assert!(heater.is_ok());
-        assert_eq!(heater.unwrap(), Some(0x0a));
+        // Thermistor correction is switched off for plain voltage measurements, then restored
+        let voltage = si7013.analog_voltage();
+        assert!(voltage.is_ok());
+        assert_eq!(voltage.unwrap().microvolts(3_300_000), -1_650_000);
+
+        i2c.done();
     }
 
     #[test]
-    fn set_heater_off() {
-        // Fill reserved bits with 1 and ensure they're written back
-        let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe7], vec![0xff]),
-            I2cTransaction::write_read(0x40, vec![0x11], vec![0xff]),
-            I2cTransaction::write(0x40, vec![0xe6, 0xfb]),
-            I2cTransaction::write(0x40, vec![0x51, 0xff]),
-        ]));
+    fn si7013_thermistor_temperature() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0x84]),
+            I2cTransaction::write(0x40, vec![0x50, 0xa4]),
+            I2cTransaction::write_read(0x40, vec![0xee], vec![0x66, 0x4c, 0x4f]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Default);
+
+        assert_eq!(si7013.thermistor_temperature(), Ok(2336));
 
-        let heater = si7021.set_heater(None);
-        assert!(heater.is_ok());
+        i2c.done();
     }
 
     #[test]
-    fn set_heater_on() {
-        // Fill reserved bits with 1 and ensure they're written back
-        let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe7], vec![0xfb]),
-            I2cTransaction::write_read(0x40, vec![0x11], vec![0xf0]),
-            I2cTransaction::write(0x40, vec![0xe6, 0xff]),
-            I2cTransaction::write(0x40, vec![0x51, 0xfa]),
-        ]));
+    fn si7013_measure_thermistor_temperature() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0x10], vec![0xa4]),
+            I2cTransaction::write_read(0x40, vec![0xee], vec![0x66, 0x4c, 0x4f]),
+        ]);
+        let mut si7013 = Si7021::new_with_chip(i2c.clone(), Chip::Si7013, SlaveAddr::Default);
+
+        assert_eq!(
+            si7013.measure_thermistor_temperature(),
+            Ok(Temperature::from_centi_celsius(2336))
+        );
 
-        let heater = si7021.set_heater(Some(0x0a));
-        assert!(heater.is_ok());
+        i2c.done();
     }
 
     #[test]
-    fn set_heater_invalid_power() {
-        // Fill reserved bits with 1 and ensure they're written back
+    fn si7021_analog_unsupported() {
+        let (mut si7021, mut i2c) = new_si7021(&[]);
+
+        assert_eq!(si7021.user_register2(), Err(Error::UnsupportedCommand));
+        assert_eq!(si7021.analog_voltage(), Err(Error::UnsupportedCommand));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn destroy_returns_bus() {
+        let si7021 = Si7021::new(I2cMock::new(&[I2cTransaction::write(0x40, vec![0xfe])]));
+
+        let mut i2c = si7021.destroy();
+        assert!(embedded_hal::i2c::I2c::write(&mut i2c, 0x40, &[0xfe]).is_ok());
+
+        i2c.done();
+    }
+
+    #[test]
+    fn i2c_mut_shares_bus() {
         let mut si7021 = Si7021::new(I2cMock::new(&[
-            I2cTransaction::write_read(0x40, vec![0xe7], vec![0xfb]),
-            I2cTransaction::write_read(0x40, vec![0x11], vec![0xf0]),
+            I2cTransaction::write(0x50, vec![0x00]),
+            I2cTransaction::write(0x40, vec![0xfe]),
         ]));
 
-        let heater = si7021.set_heater(Some(0xf0));
-        assert!(heater.is_err());
-        assert_eq!(heater.unwrap_err(), si7021_hal::Error::InvalidHeaterLevel);
+        assert!(embedded_hal::i2c::I2c::write(si7021.i2c_mut(), 0x50, &[0x00]).is_ok());
+        assert_eq!(si7021.reset(), Ok(()));
+
+        si7021.destroy().done();
+    }
+
+    #[test]
+    fn get_typed_humidity_and_temperature() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+            I2cTransaction::write_read(0x40, vec![0xe3], vec![0x66, 0x4c, 0x4f]),
+        ]);
+
+        assert_eq!(
+            si7021.measure_humidity(),
+            Ok(RelativeHumidity::from_centi_percent(7292))
+        );
+        assert_eq!(
+            si7021.read_temperature_rh_measurement(),
+            Ok(Temperature::from_centi_celsius(2334))
+        );
+        assert_eq!(
+            si7021.measure_temperature(),
+            Ok(Temperature::from_centi_celsius(2336))
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn humidity_reading_temperature() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+        ]);
+
+        let reading = si7021.humidity_reading().unwrap();
+        assert_eq!(
+            reading.humidity(),
+            RelativeHumidity::from_centi_percent(7292)
+        );
+        assert_eq!(
+            reading.temperature(),
+            Ok(Temperature::from_centi_celsius(2334))
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn read_and_write_registers() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write(0x40, vec![0x51, 0x05]),
+        ]);
+
+        let mut user_register = si7021.read_user_register().unwrap();
+        assert_eq!(user_register, UserRegister1::default());
+        user_register.set_measurement_resolution(MeasurementResolution::Rh8Temp12);
+        assert_eq!(si7021.write_user_register(user_register), Ok(()));
+
+        let mut heater_register = si7021.read_heater_register().unwrap();
+        heater_register.set_heater_level(HeaterLevel::new(5).unwrap());
+        assert_eq!(si7021.write_heater_register(heater_register), Ok(()));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn htu21d_heater_register_unsupported() {
+        let mut i2c = I2cMock::new(&[]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
+
+        assert_eq!(
+            htu21d.read_heater_register(),
+            Err(Error::UnsupportedCommand)
+        );
+        assert_eq!(
+            htu21d.write_heater_register(HeaterControlRegister::default()),
+            Err(Error::UnsupportedCommand)
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn register_cache_skips_reads() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3b]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3f]),
+            I2cTransaction::write(0x40, vec![0x51, 0x05]),
+            // VDDS is always read from the sensor
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x7f]),
+        ]);
+        si7021.set_register_cache(true);
+
+        assert_eq!(
+            si7021.set_measurement_resolution(MeasurementResolution::Rh8Temp12),
+            Ok(())
+        );
+        assert_eq!(si7021.set_heater(HeaterLevel::new(5)), Ok(()));
+        assert_eq!(si7021.heater(), Ok(HeaterLevel::new(5)));
+        assert_eq!(
+            si7021.measurement_resolution(),
+            Ok(MeasurementResolution::Rh8Temp12)
+        );
+        assert_eq!(si7021.vdd_status(), Ok(VddStatus::Low));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn register_cache_after_reset() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xfe]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0f]),
+        ]);
+        si7021.set_register_cache(true);
+
+        assert_eq!(si7021.reset(), Ok(()));
+        assert_eq!(si7021.set_heater(Some(HeaterLevel::MAX)), Ok(()));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn htu21d_register_cache_after_reset() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write(0x40, vec![0xfe]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x02]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x83]),
+        ]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
+        htu21d.set_register_cache(true);
+
+        assert_eq!(htu21d.reset(), Ok(()));
+        assert_eq!(
+            htu21d.set_measurement_resolution(MeasurementResolution::Rh11Temp11),
+            Ok(())
+        );
+        assert_eq!(
+            htu21d.measurement_resolution(),
+            Ok(MeasurementResolution::Rh11Temp11)
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn register_cache_after_reset_with_delay() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write(0x40, vec![0xfe]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x02]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x03]),
+        ]);
+        let mut htu21d = Si7021::new_with_chip(i2c.clone(), Chip::Htu21d, SlaveAddr::Default);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(15_000)]);
+        htu21d.set_register_cache(true);
+
+        // HTU21D reserved bits differ from the Si7021 default
+        assert_eq!(htu21d.reset_with_delay(&mut delay), Ok(()));
+        assert_eq!(
+            htu21d.set_measurement_resolution(MeasurementResolution::Rh8Temp12),
+            Ok(())
+        );
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn register_cache_invalidated() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x02]),
+        ]);
+        si7021.set_register_cache(true);
+
+        assert_eq!(si7021.heater(), Ok(None));
+        assert_eq!(si7021.heater(), Ok(None));
+        si7021.invalidate_cache();
+        assert_eq!(si7021.heater(), Ok(HeaterLevel::new(2)));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn register_cache_dropped_on_failed_write() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3b]).with_error(mock_error()),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+        ]);
+        si7021.set_register_cache(true);
+
+        assert!(si7021
+            .set_measurement_resolution(MeasurementResolution::Rh8Temp12)
+            .is_err());
+        assert_eq!(
+            si7021.measurement_resolution(),
+            Ok(MeasurementResolution::Rh12Temp14)
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn chip_accuracy() {
+        assert_eq!(Chip::Si7021.humidity_accuracy(), Some(300));
+        assert_eq!(Chip::Si7021.temperature_accuracy(), 40);
+        assert_eq!(Chip::Si7051.humidity_accuracy(), None);
+        assert_eq!(Chip::Si7051.temperature_accuracy(), 10);
+    }
+}
+
+#[cfg(feature = "eh02")]
+mod eh02 {
+    use embedded_hal_mock::eh0::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use embedded_hal_mock::eh0::MockError;
+    use si7021_hal::Eh02;
+    use std::io::ErrorKind;
+
+    use super::*;
+
+    fn new_si7021(expectations: &[I2cTransaction]) -> (Si7021<Eh02<I2cMock>>, I2cMock) {
+        let i2c = I2cMock::new(expectations);
+        (Si7021::new(Eh02::new(i2c.clone())), i2c)
+    }
+
+    fn mock_error() -> MockError {
+        MockError::Io(ErrorKind::Other)
+    }
+
+    si7021_tests!();
+    blocking_tests!();
+
+    #[test]
+    fn destroy_returns_bus() {
+        let si7021 = Si7021::new(Eh02::new(I2cMock::new(&[])));
+
+        si7021.destroy().into_inner().done();
+    }
+
+    // Errors from embedded-hal 0.2 buses cannot be told apart, so none counts as NACK
+    #[test]
+    fn read_humidity_bus_error() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(mock_error()),
+        ]);
+
+        assert!(si7021.start_humidity_measurement().is_ok());
+        assert_eq!(
+            si7021.read_humidity(),
+            Err(nb::Error::Other(Error::I2c(mock_error())))
+        );
+
+        i2c.done();
+    }
+
+    #[test]
+    fn humidity_no_hold_bus_error() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xf5]),
+            I2cTransaction::read(0x40, vec![0x00, 0x00, 0x00]).with_error(mock_error()),
+        ]);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_us(22_800)]);
+
+        assert_eq!(
+            si7021.humidity_no_hold(&mut delay),
+            Err(Error::I2c(mock_error()))
+        );
+
+        i2c.done();
+        delay.done();
+    }
+
+    // HAL implementing only the traits required by the embedded-hal 0.2 driver before the port
+    struct WriteReadOnly(I2cMock);
+
+    impl embedded_hal_02::blocking::i2c::Write for WriteReadOnly {
+        type Error = MockError;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), MockError> {
+            self.0.write(address, bytes)
+        }
+    }
+
+    impl embedded_hal_02::blocking::i2c::WriteRead for WriteReadOnly {
+        type Error = MockError;
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), MockError> {
+            self.0.write_read(address, bytes, buffer)
+        }
+    }
+
+    #[test]
+    fn write_read_only_bus() {
+        let mut i2c = I2cMock::new(&[I2cTransaction::write_read(
+            0x40,
+            vec![0xe3],
+            vec![0x66, 0x4c, 0x4f],
+        )]);
+        let mut si7021 = Si7021::new_eh02(WriteReadOnly(i2c.clone()));
+
+        assert_eq!(si7021.temperature(), Ok(2336));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn new_eh02() {
+        let mut i2c = I2cMock::new(&[I2cTransaction::write_read(
+            0x40,
+            vec![0xe3],
+            vec![0x66, 0x4c, 0x4f],
+        )]);
+        let mut si7021 = Si7021::new_eh02(i2c.clone());
+
+        assert_eq!(si7021.temperature(), Ok(2336));
+
+        i2c.done();
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use pollster::block_on;
+    use si7021_hal::AsyncSi7021;
+
+    use super::*;
+
+    // Drives the async driver to completion so the shared suite can call it like the blocking one
+    struct BlockingSi7021(AsyncSi7021<I2cMock>);
+
+    impl BlockingSi7021 {
+        fn humidity(&mut self) -> Result<i32, Error<ErrorKind>> {
+            block_on(self.0.humidity())
+        }
+        fn temperature_rh_measurement(&mut self) -> Result<i32, Error<ErrorKind>> {
+            block_on(self.0.temperature_rh_measurement())
+        }
+        fn temperature(&mut self) -> Result<i32, Error<ErrorKind>> {
+            block_on(self.0.temperature())
+        }
+        fn measure(&mut self) -> Result<Measurement, Error<ErrorKind>> {
+            block_on(self.0.measure())
+        }
+        fn serial_number(&mut self) -> Result<u64, Error<ErrorKind>> {
+            block_on(self.0.serial_number())
+        }
+        fn device_identity(&mut self) -> Result<DeviceId, Error<ErrorKind>> {
+            block_on(self.0.device_identity())
+        }
+        fn firmware_revision(&mut self) -> Result<u8, Error<ErrorKind>> {
+            block_on(self.0.firmware_revision())
+        }
+        fn reset(&mut self) -> Result<(), Error<ErrorKind>> {
+            block_on(self.0.reset())
+        }
+        fn current_config(&mut self) -> Result<Config, Error<ErrorKind>> {
+            block_on(self.0.current_config())
+        }
+        fn apply(&mut self, config: &Config) -> Result<(), Error<ErrorKind>> {
+            block_on(self.0.apply(config))
+        }
+        fn apply_verified(&mut self, config: &Config) -> Result<(), Error<ErrorKind>> {
+            block_on(self.0.apply_verified(config))
+        }
+        fn vdd_status(&mut self) -> Result<VddStatus, Error<ErrorKind>> {
+            block_on(self.0.vdd_status())
+        }
+        fn supply_ok(&mut self) -> Result<bool, Error<ErrorKind>> {
+            block_on(self.0.supply_ok())
+        }
+        fn set_supply_check(&mut self, enabled: bool) {
+            self.0.set_supply_check(enabled)
+        }
+        fn set_write_verify(&mut self, retries: Option<u8>) {
+            self.0.set_write_verify(retries)
+        }
+        fn measurement_resolution(&mut self) -> Result<MeasurementResolution, Error<ErrorKind>> {
+            block_on(self.0.measurement_resolution())
+        }
+        fn set_measurement_resolution(
+            &mut self,
+            measurement_resolution: MeasurementResolution,
+        ) -> Result<(), Error<ErrorKind>> {
+            block_on(self.0.set_measurement_resolution(measurement_resolution))
+        }
+        fn heater(&mut self) -> Result<Option<HeaterLevel>, Error<ErrorKind>> {
+            block_on(self.0.heater())
+        }
+        fn set_heater(
+            &mut self,
+            heater_level: Option<HeaterLevel>,
+        ) -> Result<(), Error<ErrorKind>> {
+            block_on(self.0.set_heater(heater_level))
+        }
+    }
+
+    fn new_si7021(expectations: &[I2cTransaction]) -> (BlockingSi7021, I2cMock) {
+        let i2c = I2cMock::new(expectations);
+        (BlockingSi7021(AsyncSi7021::new(i2c.clone())), i2c)
+    }
+
+    fn mock_error() -> ErrorKind {
+        ErrorKind::Other
+    }
+
+    si7021_tests!();
+
+    #[test]
+    fn destroy_returns_bus() {
+        let mut si7021 = AsyncSi7021::new(I2cMock::new(&[I2cTransaction::write(0x40, vec![0xfe])]));
+
+        assert_eq!(block_on(si7021.reset()), Ok(()));
+
+        si7021.destroy().done();
+    }
+
+    #[test]
+    fn reset_with_delay() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write(0x40, vec![0xfe]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+        ]);
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_us(15_000),
+            DelayTransaction::delay_us(1_000),
+        ]);
+
+        assert_eq!(block_on(si7021.0.reset_with_delay(&mut delay)), Ok(()));
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn device_identity_without_firmware_revision() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(
+                0x40,
+                vec![0xfa, 0x0f],
+                vec![0x84, 0xbe, 0x2c, 0x5b, 0xf9, 0x9e, 0xb1, 0xa8],
+            ),
+            I2cTransaction::write_read(
+                0x40,
+                vec![0xfc, 0xc9],
+                vec![0x15, 0xff, 0xb5, 0xff, 0xff, 0xcb],
+            ),
+            I2cTransaction::write_read(0x40, vec![0x84, 0xb8], vec![0x00])
+                .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+        ]);
+
+        let device_identity = block_on(si7021.0.device_identity()).unwrap();
+        assert_eq!(device_identity.firmware, None);
+
+        i2c.done();
+    }
+
+    #[test]
+    fn humidity_reading_temperature() {
+        let (mut si7021, mut i2c) = new_si7021(&[
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+        ]);
+
+        let reading = block_on(si7021.0.humidity_reading()).unwrap();
+        assert_eq!(
+            reading.humidity(),
+            RelativeHumidity::from_centi_percent(7292)
+        );
+        assert_eq!(
+            block_on(reading.temperature()),
+            Ok(Temperature::from_centi_celsius(2334))
+        );
+
+        i2c.done();
+    }
+}
+
+mod registers {
+    use super::*;
+
+    #[test]
+    fn user_register1_fields() {
+        let user_register = UserRegister1::default();
+        assert_eq!(user_register.bits(), 0x3a);
+        assert_eq!(
+            user_register.measurement_resolution(),
+            MeasurementResolution::Rh12Temp14
+        );
+        assert_eq!(user_register.vdd_status(), VddStatus::Ok);
+        assert!(!user_register.heater_enabled());
+
+        let user_register = UserRegister1::from_bits(0xc5);
+        assert_eq!(
+            user_register.measurement_resolution(),
+            MeasurementResolution::Rh11Temp11
+        );
+        assert_eq!(user_register.vdd_status(), VddStatus::Low);
+        assert!(user_register.heater_enabled());
+    }
+
+    #[test]
+    fn user_register1_preserves_reserved_bits() {
+        let mut user_register = UserRegister1::from_bits(0xff);
+        user_register.set_measurement_resolution(MeasurementResolution::Rh12Temp14);
+        user_register.set_heater_enabled(false);
+        assert_eq!(user_register.bits(), 0x7a);
+
+        let mut user_register = UserRegister1::from_bits(0x00);
+        user_register.set_measurement_resolution(MeasurementResolution::Rh10Temp10);
+        user_register.set_heater_enabled(true);
+        assert_eq!(user_register.bits(), 0x84);
+    }
+
+    #[test]
+    fn user_register1_round_trip() {
+        for bits in 0..=u8::MAX {
+            let mut user_register = UserRegister1::from_bits(bits);
+            user_register.set_measurement_resolution(user_register.measurement_resolution());
+            user_register.set_heater_enabled(user_register.heater_enabled());
+            assert_eq!(user_register.bits(), bits);
+        }
+    }
+
+    #[test]
+    fn heater_control_register_fields() {
+        let mut heater_register = HeaterControlRegister::from_bits(0xf3);
+        assert_eq!(heater_register.heater_level(), HeaterLevel::new(3).unwrap());
+        heater_register.set_heater_level(HeaterLevel::MAX);
+        assert_eq!(heater_register.bits(), 0xff);
+        assert_eq!(
+            HeaterControlRegister::default().heater_level(),
+            HeaterLevel::MIN
+        );
+    }
+
+    #[test]
+    fn heater_control_register_round_trip() {
+        for bits in 0..=u8::MAX {
+            let mut heater_register = HeaterControlRegister::from_bits(bits);
+            heater_register.set_heater_level(heater_register.heater_level());
+            assert_eq!(heater_register.bits(), bits);
+        }
+    }
+}
+
+mod heater {
+    use super::*;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use si7021_hal::psychrometrics::saturation_vapor_pressure;
+    use si7021_hal::{CompensatedMeasurement, HeaterCompensation};
+
+    #[test]
+    fn heater_levels() {
+        assert_eq!(HeaterLevel::new(0), Some(HeaterLevel::MIN));
+        assert_eq!(HeaterLevel::new(15), Some(HeaterLevel::MAX));
+        assert_eq!(HeaterLevel::new(16), None);
+        assert_eq!(HeaterLevel::MAX.level(), 15);
+        assert_eq!(u8::from(HeaterLevel::MAX), 15);
+    }
+
+    #[test]
+    fn heater_current() {
+        assert_eq!(HeaterLevel::MIN.microamps(), 3_090);
+        assert_eq!(HeaterLevel::new(4).unwrap().microamps(), 27_390);
+        assert_eq!(HeaterLevel::new(8).unwrap().microamps(), 51_690);
+        assert_eq!(HeaterLevel::MAX.microamps(), 94_200);
+    }
+
+    #[test]
+    fn heater_level_from_current() {
+        assert_eq!(HeaterLevel::from_microamps(3_089), None);
+        assert_eq!(HeaterLevel::from_microamps(3_090), Some(HeaterLevel::MIN));
+        assert_eq!(HeaterLevel::from_microamps(30_000), HeaterLevel::new(4));
+        assert_eq!(HeaterLevel::from_microamps(200_000), Some(HeaterLevel::MAX));
+    }
+
+    #[test]
+    fn heater_level_nearest_current() {
+        assert_eq!(HeaterLevel::nearest_microamps(0), HeaterLevel::MIN);
+        assert_eq!(
+            HeaterLevel::nearest_microamps(32_000),
+            HeaterLevel::new(5).unwrap()
+        );
+        assert_eq!(HeaterLevel::nearest_microamps(200_000), HeaterLevel::MAX);
+    }
+
+    fn compensation(level: HeaterLevel, offset: i32) -> HeaterCompensation {
+        let mut compensation = HeaterCompensation::new([Temperature::default(); 16]);
+        compensation.set_offset(level, Temperature::from_centi_celsius(offset));
+        compensation
+    }
+
+    #[test]
+    fn compensate_recovers_ambient() {
+        // 25°C and 50% RH ambient, read on a die heated by 10°C
+        let level = HeaterLevel::new(10).unwrap();
+        let ambient = Temperature::from_centi_celsius(2500);
+        let die = Temperature::from_centi_celsius(3500);
+        let die_humidity =
+            5000 * saturation_vapor_pressure(ambient) / saturation_vapor_pressure(die);
+        let measurement = Measurement {
+            humidity: RelativeHumidity::from_centi_percent(die_humidity),
+            temperature: die,
+        };
+
+        let compensated = compensation(level, 1000).compensate(measurement, Some(level));
+        assert!(compensated.compensated);
+        assert_eq!(compensated.measurement.temperature, ambient);
+        let humidity = compensated.measurement.humidity.centi_percent();
+        assert!(
+            (humidity - 5000).abs() <= 10,
+            "{} differs from 50%",
+            humidity
+        );
+    }
+
+    #[test]
+    fn calibrated_compensation_offsets() {
+        let mut compensation = HeaterCompensation::new([Temperature::default(); 16]);
+        compensation.set_offset(HeaterLevel::MAX, Temperature::from_centi_celsius(3000));
+        assert_eq!(
+            compensation.offset(HeaterLevel::MAX),
+            Temperature::from_centi_celsius(3000)
+        );
+        assert_eq!(
+            compensation.offset(HeaterLevel::MIN),
+            Temperature::default()
+        );
+    }
+
+    #[test]
+    fn compensate_heater_off() {
+        let measurement = Measurement {
+            humidity: RelativeHumidity::from_centi_percent(7292),
+            temperature: Temperature::from_centi_celsius(2334),
+        };
+        assert_eq!(
+            compensation(HeaterLevel::MIN, 50).compensate(measurement, None),
+            CompensatedMeasurement {
+                measurement,
+                compensated: false,
+            }
+        );
+    }
+
+    #[test]
+    fn measure_compensated_heater_on() {
+        let mut i2c = I2cMock::new(&[
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x0a]),
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0x35, 0x40, 0xf8]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x87, 0x2c]),
+        ]);
+        let mut si7021 = Si7021::new(i2c.clone());
+
+        // 20% RH at 45.93°C on the die, which level 10 heats by 15.93°C
+        assert_eq!(
+            si7021.measure_compensated(&compensation(HeaterLevel::new(10).unwrap(), 1593)),
+            Ok(CompensatedMeasurement {
+                measurement: Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(4747),
+                    temperature: Temperature::from_centi_celsius(3000),
+                },
+                compensated: true,
+            })
+        );
+
+        i2c.done();
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn heater_current_float() {
+        assert_eq!(HeaterLevel::MAX.milliamps(), 94.2);
+    }
+}
+
+mod recovery {
+    use super::*;
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use si7021_hal::{CondensationRecovery, ReadingStatus, RecoveryConfig};
+    use std::cell::Cell;
+
+    fn config() -> RecoveryConfig {
+        RecoveryConfig {
+            saturation_ms: 1_000,
+            heater_level: HeaterLevel::new(0x0a).unwrap(),
+            heating_ms: 2_000,
+            cooldown_ms: 1_000,
+            ..RecoveryConfig::default()
+        }
+    }
+
+    fn saturated() -> [I2cTransaction; 2] {
+        [
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xe0, 0x00, 0x76]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+        ]
+    }
+
+    fn ambient() -> [I2cTransaction; 2] {
+        [
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write_read(0x40, vec![0xe0], vec![0x66, 0x44]),
+        ]
+    }
+
+    #[test]
+    fn heat_and_dry_cycle() {
+        let mut expectations = vec![];
+        expectations.extend(saturated());
+        expectations.extend(saturated());
+        expectations.extend([
+            // Heater found switched off, then switched on at the configured level
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0a]),
+        ]);
+        expectations.extend(saturated());
+        expectations.extend([
+            // Heater switched off and its previous level restored after heating
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x0a]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3a]),
+            I2cTransaction::write(0x40, vec![0x51, 0x00]),
+        ]);
+        expectations.extend(ambient());
+        expectations.extend(ambient());
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let time = Cell::new(0);
+        let mut recovery = CondensationRecovery::new(config(), || time.get());
+
+        let mut status_at = |now| {
+            time.set(now);
+            recovery.measure(&mut si7021).map(|reading| reading.status)
+        };
+        assert_eq!(status_at(0), Ok(ReadingStatus::Saturated));
+        assert_eq!(status_at(1_000), Ok(ReadingStatus::Saturated));
+        assert_eq!(status_at(2_000), Ok(ReadingStatus::Heating));
+        assert_eq!(status_at(3_000), Ok(ReadingStatus::CoolingDown));
+        assert_eq!(status_at(4_000), Ok(ReadingStatus::Valid));
+
+        i2c.done();
+    }
+
+    #[test]
+    fn saturation_must_be_sustained() {
+        let mut expectations = vec![];
+        expectations.extend(saturated());
+        expectations.extend(ambient());
+        expectations.extend(saturated());
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let time = Cell::new(0);
+        let mut recovery = CondensationRecovery::new(config(), || time.get());
+
+        assert_eq!(
+            recovery.measure(&mut si7021).map(|reading| reading.valid()),
+            Ok(None)
+        );
+        time.set(500);
+        let reading = recovery.measure(&mut si7021).unwrap();
+        assert_eq!(
+            reading.valid().map(|measurement| measurement.humidity),
+            Some(RelativeHumidity::from_centi_percent(7292))
+        );
+        time.set(1_200);
+        assert_eq!(
+            recovery.measure(&mut si7021).map(|reading| reading.status),
+            Ok(ReadingStatus::Saturated)
+        );
+        assert!(!recovery.is_recovering());
+
+        i2c.done();
+    }
+
+    #[test]
+    fn abort_restores_heater() {
+        let mut expectations = vec![];
+        expectations.extend(saturated());
+        expectations.extend([
+            // Heater found on at level 2
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x02]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x02]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0a]),
+            // Restored on abort
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x0a]),
+            I2cTransaction::write(0x40, vec![0x51, 0x02]),
+        ]);
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let config = RecoveryConfig {
+            saturation_ms: 0,
+            ..config()
+        };
+        let mut recovery = CondensationRecovery::new(config, || 0);
+
+        assert_eq!(
+            recovery.measure(&mut si7021).map(|reading| reading.status),
+            Ok(ReadingStatus::Saturated)
+        );
+        assert!(recovery.is_recovering());
+        assert_eq!(recovery.abort(&mut si7021), Ok(()));
+        assert!(!recovery.is_recovering());
+
+        i2c.done();
+    }
+
+    #[test]
+    fn failed_heater_write_is_restored() {
+        let mut expectations = vec![];
+        expectations.extend(saturated());
+        expectations.extend([
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3a]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            // Heater enabled, but its level not written
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0a])
+                .with_error(embedded_hal::i2c::ErrorKind::Bus),
+            // Switched off again on abort
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![0x3e]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![0x00]),
+            I2cTransaction::write(0x40, vec![0xe6, 0x3a]),
+        ]);
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let config = RecoveryConfig {
+            saturation_ms: 0,
+            ..config()
+        };
+        let mut recovery = CondensationRecovery::new(config, || 0);
+
+        assert_eq!(
+            recovery.measure(&mut si7021).map(|reading| reading.status),
+            Err(Error::I2c(embedded_hal::i2c::ErrorKind::Bus))
+        );
+        assert!(recovery.is_recovering());
+        assert_eq!(recovery.abort(&mut si7021), Ok(()));
+
+        i2c.done();
+    }
+}
+
+mod selftest {
+    use super::*;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use si7021_hal::{SelfTestConfig, SelfTestReport};
+
+    fn baseline() -> [I2cTransaction; 2] {
+        [
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0xa1, 0xa6, 0x51]),
+            I2cTransaction::write_read(0x40, vec![0xe3], vec![0x66, 0x4c, 0x4f]),
+        ]
+    }
+
+    fn registers(user_register: u8, heater_register: u8) -> [I2cTransaction; 2] {
+        [
+            I2cTransaction::write_read(0x40, vec![0xe7], vec![user_register]),
+            I2cTransaction::write_read(0x40, vec![0x11], vec![heater_register]),
+        ]
+    }
+
+    fn heater_on() -> Vec<I2cTransaction> {
+        let mut expectations = vec![];
+        expectations.extend(registers(0x3a, 0x00));
+        expectations.extend(registers(0x3a, 0x00));
+        expectations.extend([
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0f]),
+        ]);
+        expectations
+    }
+
+    fn heater_off() -> Vec<I2cTransaction> {
+        let mut expectations = vec![];
+        expectations.extend(registers(0x3e, 0x0f));
+        expectations.extend([
+            I2cTransaction::write(0x40, vec![0xe6, 0x3a]),
+            I2cTransaction::write(0x40, vec![0x51, 0x00]),
+        ]);
+        expectations
+    }
+
+    fn temperature(bytes: [u8; 3]) -> I2cTransaction {
+        I2cTransaction::write_read(0x40, vec![0xe3], bytes.to_vec())
+    }
+
+    fn config(samples: u8) -> SelfTestConfig {
+        SelfTestConfig {
+            samples,
+            interval_ms: 500,
+            ..SelfTestConfig::default()
+        }
+    }
+
+    #[test]
+    fn heater_self_test_passes() {
+        let mut expectations = vec![];
+        expectations.extend(baseline());
+        expectations.extend(heater_on());
+        expectations.extend([
+            temperature([0x6d, 0x0c, 0x68]),
+            temperature([0x6f, 0xf4, 0x8a]),
+            temperature([0x6e, 0x00, 0x38]),
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0x87, 0x2c, 0x76]),
+            temperature([0x6e, 0x00, 0x38]),
+        ]);
+        expectations.extend(heater_off());
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(500),
+            DelayTransaction::delay_ms(500),
+            DelayTransaction::delay_ms(500),
+        ]);
+
+        assert_eq!(
+            si7021.heater_self_test(&config(3), &mut delay),
+            Ok(SelfTestReport {
+                passed: true,
+                baseline: Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(7292),
+                    temperature: Temperature::from_centi_celsius(2336),
+                },
+                heated: Measurement {
+                    humidity: RelativeHumidity::from_centi_percent(6000),
+                    temperature: Temperature::from_centi_celsius(2999),
+                },
+                temperature_rise: Temperature::from_centi_celsius(663),
+                humidity_drop: RelativeHumidity::from_centi_percent(1292),
+            })
+        );
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn heater_self_test_stale_readings_fail() {
+        let mut expectations = vec![];
+        expectations.extend(baseline());
+        expectations.extend(heater_on());
+        expectations.push(temperature([0x66, 0x4c, 0x4f]));
+        expectations.extend(baseline());
+        expectations.extend(heater_off());
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(500)]);
+
+        let report = si7021.heater_self_test(&config(1), &mut delay).unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.temperature_rise, Temperature::default());
+        assert_eq!(report.humidity_drop, RelativeHumidity::default());
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn heater_self_test_restores_enabled_heater() {
+        let mut expectations = vec![];
+        expectations.extend(baseline());
+        expectations.extend(registers(0x3e, 0x04));
+        expectations.extend(registers(0x3e, 0x04));
+        expectations.extend([
+            I2cTransaction::write(0x40, vec![0xe6, 0x3e]),
+            I2cTransaction::write(0x40, vec![0x51, 0x0f]),
+            temperature([0x6d, 0x0c, 0x68]),
+            I2cTransaction::write_read(0x40, vec![0xe5], vec![0x87, 0x2c, 0x76]),
+            temperature([0x6e, 0x00, 0x38]),
+        ]);
+        expectations.extend(registers(0x3e, 0x0f));
+        expectations.push(I2cTransaction::write(0x40, vec![0x51, 0x04]));
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(500)]);
+
+        assert!(
+            si7021
+                .heater_self_test(&config(1), &mut delay)
+                .unwrap()
+                .passed
+        );
+
+        i2c.done();
+        delay.done();
+    }
+
+    #[test]
+    fn heater_self_test_restores_heater_on_error() {
+        let mut expectations = vec![];
+        expectations.extend(baseline());
+        expectations.extend(heater_on());
+        expectations.push(temperature([0x66, 0x4c, 0xff]));
+        expectations.extend(heater_off());
+        let mut i2c = I2cMock::new(&expectations);
+        let mut si7021 = Si7021::new(i2c.clone());
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(500)]);
+
+        assert_eq!(
+            si7021.heater_self_test(&config(2), &mut delay),
+            Err(Error::ChecksumFailure)
+        );
+
+        i2c.done();
+        delay.done();
+    }
+}
+
+mod units {
+    use super::*;
+
+    #[test]
+    fn temperature_conversions() {
+        let temperature = Temperature::from_centi_celsius(2315);
+        assert_eq!(temperature.centi_celsius(), 2315);
+        assert_eq!(temperature.centi_fahrenheit(), 7367);
+        assert_eq!(temperature.centi_kelvin(), 29630);
+        assert_eq!(Temperature::from_centi_fahrenheit(7367), temperature);
+        assert_eq!(
+            Temperature::from_centi_fahrenheit(-400),
+            Temperature::from_centi_celsius(-2000)
+        );
+        assert_eq!(
+            Temperature::from_centi_celsius(2316).centi_fahrenheit(),
+            7369
+        );
+        assert_eq!(Temperature::from_centi_celsius(-1).centi_fahrenheit(), 3198);
+        assert_eq!(
+            Temperature::from_centi_fahrenheit(i32::MAX),
+            Temperature::from_centi_celsius(1_193_044_693)
+        );
+        assert_eq!(
+            Temperature::from_centi_fahrenheit(i32::MIN),
+            Temperature::from_centi_celsius(-1_193_048_249)
+        );
+        assert_eq!(
+            Temperature::from_raw(0x664c),
+            temperature + Temperature::from_centi_celsius(21)
+        );
+        assert_eq!(
+            Temperature::from_raw(Temperature::from_raw(0x664c).raw()),
+            Temperature::from_raw(0x664c)
+        );
+    }
+
+    #[test]
+    fn humidity_conversions() {
+        assert_eq!(RelativeHumidity::from_raw(0xa1a6).centi_percent(), 7292);
+        assert_eq!(RelativeHumidity::from_raw(0x0000).centi_percent(), 0);
+        assert_eq!(RelativeHumidity::from_raw(0xffff).centi_percent(), 10000);
+        assert_eq!(
+            RelativeHumidity::from_raw(RelativeHumidity::from_raw(0xa1a6).raw()),
+            RelativeHumidity::from_raw(0xa1a6)
+        );
+    }
+
+    #[test]
+    fn arithmetic_and_ordering() {
+        let mut temperature = Temperature::from_centi_celsius(-150);
+        temperature += Temperature::from_centi_celsius(200);
+        assert_eq!(temperature, Temperature::from_centi_celsius(50));
+        temperature -= Temperature::from_centi_celsius(100);
+        assert!(temperature < Temperature::from_centi_celsius(0));
+
+        let humidity =
+            RelativeHumidity::from_centi_percent(5000) - RelativeHumidity::from_centi_percent(1234);
+        assert_eq!(humidity, RelativeHumidity::from_centi_percent(3766));
+        assert!(humidity > RelativeHumidity::from_centi_percent(3765));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Temperature::from_centi_celsius(2315).to_string(), "23.15°C");
+        assert_eq!(Temperature::from_centi_celsius(-5).to_string(), "-0.05°C");
+        assert_eq!(
+            Temperature::from_centi_celsius(-1250).to_string(),
+            "-12.50°C"
+        );
+        assert_eq!(
+            RelativeHumidity::from_centi_percent(7292).to_string(),
+            "72.92%RH"
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn float_conversions() {
+        let temperature = Temperature::from_centi_celsius(2500);
+        assert_eq!(temperature.celsius(), 25.0);
+        assert_eq!(temperature.fahrenheit(), 77.0);
+        assert_eq!(temperature.kelvin(), 298.15);
+        assert_eq!(RelativeHumidity::from_centi_percent(4250).percent(), 42.5);
+    }
+}
+
+mod psychrometrics {
+    use super::*;
+    use si7021_hal::psychrometrics::*;
+
+    // Saturation vapor pressure in Pa from the CRC Handbook of Chemistry and Physics
+    const SATURATION_WATER: &[(i32, i32)] = &[
+        (-1000, 287),
+        (0, 611),
+        (2000, 2339),
+        (2500, 3169),
+        (3000, 4246),
+        (4000, 7384),
+    ];
+    const SATURATION_ICE: &[(i32, i32)] = &[(-2000, 103), (-1000, 260), (0, 611)];
+
+    // Values within 0.5% of the reference table
+    fn assert_pressure(actual: i32, expected: i32) {
+        assert!(
+            (actual - expected).abs() * 200 <= expected,
+            "{} Pa differs from reference {} Pa",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn saturation_vapor_pressure_reference() {
+        for &(temperature, pressure) in SATURATION_WATER {
+            assert_pressure(saturation_vapor_pressure(celsius(temperature)), pressure);
+        }
+        for &(temperature, pressure) in SATURATION_ICE {
+            assert_pressure(
+                saturation_vapor_pressure_ice(celsius(temperature)),
+                pressure,
+            );
+        }
+    }
+
+    #[test]
+    fn saturation_vapor_pressure_magnus() {
+        assert_eq!(saturation_vapor_pressure(celsius(0)), 611);
+        assert_eq!(saturation_vapor_pressure(celsius(2500)), 3160);
+        assert_eq!(saturation_vapor_pressure(celsius(-2000)), 126);
+        assert_eq!(saturation_vapor_pressure_ice(celsius(-2000)), 103);
+    }
+
+    #[test]
+    fn out_of_range_inputs_are_clamped() {
+        assert_eq!(
+            saturation_vapor_pressure(celsius(-24312)),
+            saturation_vapor_pressure(celsius(-4685))
+        );
+        assert_eq!(
+            dew_point(percent(i32::MAX), celsius(2500)),
+            dew_point(percent(10000), celsius(2500))
+        );
+        for &temperature in &[i32::MIN, -27262, -24312, i32::MAX] {
+            for &humidity in &[i32::MIN, -1, 0, 10000, i32::MAX] {
+                let (humidity, temperature) = (percent(humidity), celsius(temperature));
+                saturation_vapor_pressure(temperature);
+                saturation_vapor_pressure_ice(temperature);
+                vapor_pressure(humidity, temperature);
+                dew_point(humidity, temperature);
+                frost_point(humidity, temperature);
+                absolute_humidity(humidity, temperature);
+                mixing_ratio(humidity, temperature, Some(i32::MIN));
+                specific_humidity(humidity, temperature, Some(i32::MAX));
+            }
+        }
+    }
+
+    #[test]
+    fn vapor_pressure_from_humidity() {
+        assert_eq!(vapor_pressure(percent(5000), celsius(2500)), 1580);
+        assert_eq!(vapor_pressure(percent(0), celsius(2500)), 0);
+    }
+
+    // Dew points from the NOAA dew point calculator, within 0.05°C
+    #[test]
+    fn dew_point_reference() {
+        for &(humidity, temperature, expected) in &[
+            (5000, 2500, 1385),
+            (6000, 2000, 1200),
+            (8000, 3000, 2617),
+            (1000, 3500, -117),
+        ] {
+            let dew_point = dew_point(percent(humidity), celsius(temperature));
+            assert!(dew_point.is_some());
+            let dew_point = dew_point.unwrap().centi_celsius();
+            assert!(
+                (dew_point - expected).abs() <= 5,
+                "{} differs from reference {}",
+                dew_point,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn dew_point_saturated() {
+        assert_eq!(
+            dew_point(percent(10000), celsius(1500)),
+            Some(celsius(1500))
+        );
+    }
+
+    #[test]
+    fn dew_point_dry_air() {
+        assert_eq!(dew_point(percent(0), celsius(2500)), None);
+        assert_eq!(frost_point(percent(0), celsius(-1000)), None);
+    }
+
+    #[test]
+    fn frost_point_below_freezing() {
+        let frost_point = frost_point(percent(8000), celsius(-1000)).unwrap();
+        let dew_point = dew_point(percent(8000), celsius(-1000)).unwrap();
+        assert_eq!(frost_point, celsius(-1139));
+        assert_eq!(dew_point, celsius(-1280));
+    }
+
+    #[test]
+    fn absolute_humidity_from_reading() {
+        assert_eq!(absolute_humidity(percent(5000), celsius(2500)), 1148);
+        assert_eq!(absolute_humidity(percent(8000), celsius(3000)), 2421);
+        assert_eq!(absolute_humidity(percent(10000), celsius(-1000)), 236);
+        assert_eq!(absolute_humidity(percent(0), celsius(2500)), 0);
+    }
+
+    #[test]
+    fn mixing_ratio_standard_atmosphere() {
+        assert_eq!(mixing_ratio(percent(5000), celsius(2500), None), 985);
+        assert_eq!(mixing_ratio(percent(8000), celsius(3000), None), 2151);
+        assert_eq!(
+            mixing_ratio(percent(3000), celsius(0), Some(STANDARD_PRESSURE)),
+            113
+        );
+    }
+
+    #[test]
+    fn mixing_ratio_with_pressure() {
+        assert_eq!(
+            mixing_ratio(percent(5000), celsius(2500), Some(90_000)),
+            1111
+        );
+    }
+
+    #[test]
+    fn specific_humidity_standard_atmosphere() {
+        assert_eq!(specific_humidity(percent(5000), celsius(2500), None), 976);
+        assert_eq!(specific_humidity(percent(8000), celsius(3000), None), 2106);
+        assert_eq!(specific_humidity(percent(10000), celsius(-1000), None), 176);
+    }
+
+    #[test]
+    fn specific_humidity_with_pressure() {
+        assert_eq!(
+            specific_humidity(percent(5000), celsius(2500), Some(90_000)),
+            1099
+        );
+    }
+
+    #[test]
+    fn moisture_content_without_pressure_difference() {
+        assert_eq!(
+            mixing_ratio(percent(5000), celsius(2500), Some(0)),
+            i32::MAX
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn float_variants() {
+        assert!((saturation_vapor_pressure_f32(25.0) - 3160.06).abs() < 0.1);
+        assert!((saturation_vapor_pressure_ice_f32(-20.0) - 103.26).abs() < 0.01);
+        assert!((vapor_pressure_f32(50.0, 25.0) - 1580.03).abs() < 0.1);
+        assert!((dew_point_f32(50.0, 25.0) - 13.85).abs() < 0.01);
+        assert!((frost_point_f32(80.0, -10.0) + 11.39).abs() < 0.01);
+        assert!((absolute_humidity_f32(50.0, 25.0) - 11.48).abs() < 0.01);
+        assert!((mixing_ratio_f32(50.0, 25.0, None) - 9.85).abs() < 0.01);
+        assert!((specific_humidity_f32(50.0, 25.0, Some(90_000.0)) - 10.99).abs() < 0.01);
+    }
+}
+
+mod comfort {
+    use super::*;
+    use si7021_hal::comfort::*;
+
+    #[test]
+    fn heat_index_simple_formula() {
+        assert_eq!(heat_index(percent(5000), celsius(2000)), celsius(1936));
+        assert_eq!(heat_index(percent(4000), celsius(2500)), celsius(2460));
+    }
+
+    #[test]
+    fn heat_index_regression() {
+        assert_eq!(heat_index(percent(7000), celsius(3000)), celsius(3504));
+        assert_eq!(heat_index(percent(5000), celsius(3500)), celsius(4068));
+        assert_eq!(heat_index(percent(6000), celsius(4500)), celsius(8561));
+    }
+
+    #[test]
+    fn heat_index_dry_adjustment() {
+        assert_eq!(heat_index(percent(1000), celsius(4000)), celsius(3671));
+    }
+
+    #[test]
+    fn heat_index_humid_adjustment() {
+        assert_eq!(heat_index(percent(9000), celsius(2900)), celsius(3723));
+    }
+
+    #[test]
+    fn humidex_from_reading() {
+        assert_eq!(humidex(percent(7000), celsius(3000)), celsius(4091));
+        assert_eq!(humidex(percent(5000), celsius(2000)), celsius(2092));
+    }
+
+    #[test]
+    fn apparent_temperature_indoors() {
+        assert_eq!(
+            apparent_temperature(percent(7000), celsius(3000), 0),
+            celsius(3578)
+        );
+    }
+
+    #[test]
+    fn extreme_inputs_are_clamped() {
+        let hottest = celsius(12886);
+        let coldest = celsius(-4685);
+        for (humidity, temperature, clamped) in [
+            (percent(20000), celsius(i32::MAX), (percent(10000), hottest)),
+            (
+                percent(i32::MAX),
+                celsius(i32::MIN),
+                (percent(10000), coldest),
+            ),
+            (percent(i32::MIN), celsius(i32::MAX), (percent(0), hottest)),
+        ] {
+            let (clamped_humidity, clamped_temperature) = clamped;
+            assert_eq!(
+                heat_index(humidity, temperature),
+                heat_index(clamped_humidity, clamped_temperature)
+            );
+            assert_eq!(
+                humidex(humidity, temperature),
+                humidex(clamped_humidity, clamped_temperature)
+            );
+            assert_eq!(
+                apparent_temperature(humidity, temperature, 0),
+                apparent_temperature(clamped_humidity, clamped_temperature, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn apparent_temperature_with_wind() {
+        assert_eq!(
+            apparent_temperature(percent(7000), celsius(3000), 300),
+            celsius(3368)
+        );
     }
 }